@@ -0,0 +1,125 @@
+// Per-frame cost of spawning and colliding with flowers, at increasing flower counts. Everything
+// runs headlessly, so these can run on any machine:
+//
+//   cargo bench --bench flowers
+
+use std::time::{Duration, Instant};
+
+use bevy::{
+    asset::Assets,
+    ecs::{event::Events, system::RunSystemOnce},
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use super_waddle::{
+    assets::GameConfigFile,
+    enemy::{detect_proximity, init_flowers, PickFlower},
+    headless::HeadlessApp,
+};
+
+const FLOWER_COUNTS: [u32; 3] = [1_000, 10_000, 100_000];
+
+fn config(num_flowers: u32) -> GameConfigFile {
+    GameConfigFile {
+        schema: None,
+        player_move_speed: 250.,
+        world_size: 1024.,
+        num_flowers,
+        flower_pickup_range: 16.,
+        // Long enough that no benchmark reaches the end of the game
+        game_time_seconds: 1_000_000.,
+        simulation_hz: 64.,
+    }
+}
+
+// An app that has gone through its first frame, so the player and flowers exist.
+fn started_app(num_flowers: u32) -> HeadlessApp {
+    let mut app = HeadlessApp::new(config(num_flowers));
+    app.step(1);
+    app
+}
+
+fn bench_init_flowers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("init_flowers");
+    group.sample_size(10);
+
+    for num_flowers in FLOWER_COUNTS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_flowers),
+            &num_flowers,
+            |b, &num_flowers| {
+                // Start from a world with no flowers in it, then spawn them all
+                b.iter_batched(
+                    || {
+                        let mut app = started_app(0);
+                        app.world_mut()
+                            .resource_mut::<Assets<GameConfigFile>>()
+                            .iter_mut()
+                            .for_each(|(_, config)| config.num_flowers = num_flowers);
+                        app
+                    },
+                    // Hand the app back so dropping it isn't timed
+                    |mut app| {
+                        app.world_mut().run_system_once(init_flowers);
+                        app
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_detect_proximity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("detect_proximity");
+
+    for num_flowers in FLOWER_COUNTS {
+        let mut app = started_app(num_flowers);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_flowers),
+            &num_flowers,
+            |b, _| {
+                // Events from earlier iterations would otherwise pile up, since nothing reads them
+                b.iter_custom(|iters| {
+                    let mut elapsed = Duration::ZERO;
+                    for _ in 0..iters {
+                        app.world_mut().resource_mut::<Events<PickFlower>>().clear();
+                        let start = Instant::now();
+                        app.world_mut().run_system_once(detect_proximity);
+                        elapsed += start.elapsed();
+                    }
+                    elapsed
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+// A whole frame, to see how much of it the flowers account for
+fn bench_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("frame");
+
+    for num_flowers in FLOWER_COUNTS {
+        let mut app = started_app(num_flowers);
+
+        group.bench_with_input(
+            BenchmarkId::from_parameter(num_flowers),
+            &num_flowers,
+            |b, _| b.iter(|| app.step(1)),
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_init_flowers,
+    bench_detect_proximity,
+    bench_frame
+);
+criterion_main!(benches);
@@ -0,0 +1,195 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::aseprite::AsepriteLoader;
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SpriteSheet>()
+            .init_asset_loader::<AsepriteLoader>()
+            .add_systems(
+                Update,
+                (animate_sprites, despawn_finished_animations).chain(),
+            );
+    }
+}
+
+// A texture atlas plus the frame timings and named tags (e.g. "Walk") of an Aseprite file
+#[derive(Debug, TypePath, Asset)]
+pub struct SpriteSheet {
+    #[dependency]
+    pub texture: Handle<Image>,
+    #[dependency]
+    pub layout: Handle<TextureAtlasLayout>,
+    pub durations: Vec<Duration>,
+    // The frames each tag plays, in order, with the tag's direction already applied
+    pub tags: HashMap<String, Vec<usize>>,
+    // Named areas of the sprite, in pixels from its top left corner
+    pub slices: HashMap<String, Rect>,
+}
+
+impl SpriteSheet {
+    // Sprites with a tag the sheet doesn't have just show its first frame.
+    fn frames(&self, tag: &str) -> &[usize] {
+        self.tags.get(tag).map(Vec::as_slice).unwrap_or(&[0])
+    }
+
+    // How long the tag takes to play through once, if the sheet has it
+    pub fn tag_duration(&self, tag: &str) -> Option<Duration> {
+        let frames = self.tags.get(tag)?;
+        Some(frames.iter().map(|&frame| self.durations[frame]).sum())
+    }
+}
+
+// Plays a tag from a `SpriteSheet` on a sprite, by setting its texture atlas index.
+#[derive(Debug, Component)]
+pub struct SpriteAnimation {
+    sheet: Handle<SpriteSheet>,
+    tag: String,
+    repeat: bool,
+    // Position in the tag's frames, and how long it's been showing
+    frame: usize,
+    elapsed: Duration,
+    finished: bool,
+}
+
+impl SpriteAnimation {
+    // Loops the tag until something else is played.
+    pub fn repeating(sheet: Handle<SpriteSheet>, tag: &str) -> Self {
+        Self {
+            sheet,
+            tag: tag.to_string(),
+            repeat: true,
+            frame: 0,
+            elapsed: Duration::ZERO,
+            finished: false,
+        }
+    }
+
+    // Plays the tag once, then stays on its last frame.
+    pub fn once(sheet: Handle<SpriteSheet>, tag: &str) -> Self {
+        Self {
+            repeat: false,
+            ..Self::repeating(sheet, tag)
+        }
+    }
+
+    // Starts part of the way through, so sprites spawned together don't all move in step.
+    pub fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = elapsed;
+        self
+    }
+
+    // Switches to another tag, from its start. Playing the current tag again does nothing.
+    pub fn play(&mut self, tag: &str) {
+        if self.tag == tag {
+            return;
+        }
+        self.tag = tag.to_string();
+        self.frame = 0;
+        self.elapsed = Duration::ZERO;
+        self.finished = false;
+    }
+
+    pub fn sheet(&self) -> &Handle<SpriteSheet> {
+        &self.sheet
+    }
+
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // Moves the animation on by `delta`, returning the atlas index to show.
+    fn advance(&mut self, sheet: &SpriteSheet, delta: Duration) -> usize {
+        let frames = sheet.frames(&self.tag);
+        self.frame = self.frame.min(frames.len() - 1);
+        self.elapsed += delta;
+
+        loop {
+            let index = frames[self.frame];
+            let duration = sheet.durations[index];
+            if self.finished || self.elapsed < duration {
+                return index;
+            }
+
+            self.elapsed -= duration;
+            if self.frame + 1 < frames.len() {
+                self.frame += 1;
+            } else if self.repeat {
+                self.frame = 0;
+            } else {
+                self.finished = true;
+            }
+        }
+    }
+}
+
+// Removes the entity once its animation has played through, e.g. for one-off effects.
+#[derive(Default, Component)]
+pub struct DespawnWhenFinished;
+
+fn animate_sprites(
+    time: Res<Time>,
+    sheets: Res<Assets<SpriteSheet>>,
+    mut query: Query<(&mut SpriteAnimation, &mut TextureAtlas, &mut Handle<Image>)>,
+) {
+    for (mut animation, mut atlas, mut texture) in &mut query {
+        let Some(sheet) = sheets.get(&animation.sheet) else {
+            continue;
+        };
+
+        // Also picks up a new atlas when the sheet is hot reloaded
+        if *texture != sheet.texture {
+            *texture = sheet.texture.clone();
+        }
+        if atlas.layout != sheet.layout {
+            atlas.layout = sheet.layout.clone();
+        }
+
+        let index = animation.advance(sheet, time.delta());
+        if atlas.index != index {
+            atlas.index = index;
+        }
+    }
+}
+
+fn despawn_finished_animations(
+    mut commands: Commands,
+    query: Query<(Entity, &SpriteAnimation), With<DespawnWhenFinished>>,
+) {
+    for (entity, animation) in &query {
+        if animation.is_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// The order a tag plays its frames in
+#[derive(Default, Debug, Clone, Copy)]
+pub enum TagDirection {
+    #[default]
+    Forward,
+    Reverse,
+    PingPong,
+    PingPongReverse,
+}
+
+impl TagDirection {
+    // The order the frames from..=to are played in. Ping-pong doesn't repeat the frames at either
+    // end, so it loops smoothly.
+    pub fn frames(self, from: usize, to: usize) -> Vec<usize> {
+        match self {
+            TagDirection::Forward => (from..=to).collect(),
+            TagDirection::Reverse => (from..=to).rev().collect(),
+            TagDirection::PingPong => (from..=to).chain((from + 1..to).rev()).collect(),
+            TagDirection::PingPongReverse => (from..=to).rev().chain(from + 1..to).collect(),
+        }
+    }
+}
@@ -0,0 +1,656 @@
+use std::{io::Read, time::Duration};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::{thiserror::Error, BoxedFuture},
+};
+use flate2::read::ZlibDecoder;
+
+use crate::animation::{SpriteSheet, TagDirection};
+
+// Loads `.aseprite` files as they're saved by Aseprite, so there's no need to export a sprite sheet
+// first. Every frame is flattened into one atlas texture, and the tags and slices come along with
+// it. See https://github.com/aseprite/aseprite/blob/main/docs/ase-file-specs.md for the format.
+#[derive(Default)]
+pub struct AsepriteLoader;
+
+impl AssetLoader for AsepriteLoader {
+    type Asset = SpriteSheet;
+    type Settings = ();
+    type Error = AsepriteLoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        (): &'a Self::Settings,
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let file = match AsepriteFile::parse(&bytes) {
+                Ok(file) => file,
+                Err(err) => {
+                    error!(
+                        "Unable to parse {}: {:?}",
+                        load_context.path().display(),
+                        err
+                    );
+                    return Err(err);
+                }
+            };
+
+            let (image, layout) = file.atlas();
+
+            Ok(SpriteSheet {
+                texture: load_context.add_labeled_asset("texture".to_string(), image),
+                layout: load_context.add_labeled_asset("layout".to_string(), layout),
+                durations: file.frames.iter().map(|frame| frame.duration).collect(),
+                tags: file
+                    .tags
+                    .iter()
+                    .map(|tag| (tag.name.clone(), tag.direction.frames(tag.from, tag.to)))
+                    .collect(),
+                slices: file
+                    .slices
+                    .iter()
+                    .map(|slice| (slice.name.clone(), slice.bounds))
+                    .collect(),
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum AsepriteLoadError {
+    #[error("reading asset")]
+    ReadError(#[from] std::io::Error),
+    #[error("not an aseprite file")]
+    NotAseprite,
+    #[error("file has no frames")]
+    NoFrames,
+    #[error("file ends part way through")]
+    Truncated,
+    #[error("unsupported color depth {0}")]
+    UnsupportedColorDepth(u16),
+    #[error("failed to decompress cel")]
+    DecompressError(std::io::Error),
+    #[error("tag '{0}' refers to frames the file doesn't have")]
+    TagOutOfRange(String),
+    #[error("cel in frame {frame} on layer {layer} links to a cel that doesn't exist or loops")]
+    BrokenLink { frame: usize, layer: usize },
+    #[error("cel is too large")]
+    CelTooLarge,
+    #[error("canvas is {width}x{height}, which is too large")]
+    CanvasTooLarge { width: u32, height: u32 },
+    #[error("frames need a {width}x{height} atlas, which is too large")]
+    AtlasTooLarge { width: u32, height: u32 },
+}
+
+// Far beyond any sprite we'd draw. Without a limit a broken file could ask for gigabytes of pixels.
+pub const MAX_CANVAS_SIZE: u32 = 4096;
+// The largest texture every GPU Bevy runs on can hold
+pub const MAX_ATLAS_SIZE: u32 = 8192;
+
+// The parts of an Aseprite file needed to draw its frames
+#[derive(Debug)]
+pub struct AsepriteFile {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<Layer>,
+    pub frames: Vec<Frame>,
+    pub tags: Vec<Tag>,
+    pub slices: Vec<Slice>,
+    color_depth: u16,
+    palette: Vec<[u8; 4]>,
+    transparent_index: u8,
+}
+
+#[derive(Debug)]
+pub struct Layer {
+    pub name: String,
+    pub visible: bool,
+    // Layers inside a group are one level deeper than the group
+    pub child_level: u16,
+    pub opacity: u8,
+    // Groups and tilemaps have no pixels of their own to draw
+    pub is_image: bool,
+}
+
+#[derive(Debug)]
+pub struct Frame {
+    pub duration: Duration,
+    cels: Vec<Cel>,
+}
+
+#[derive(Debug)]
+struct Cel {
+    layer: usize,
+    x: i32,
+    y: i32,
+    opacity: u8,
+    content: CelContent,
+}
+
+#[derive(Debug)]
+enum CelContent {
+    // Pixels in the file's color depth
+    Image {
+        width: u32,
+        height: u32,
+        pixels: Vec<u8>,
+    },
+    // The same cel as the given frame
+    Linked(usize),
+    Tilemap,
+}
+
+#[derive(Debug)]
+pub struct Tag {
+    pub name: String,
+    pub from: usize,
+    pub to: usize,
+    pub direction: TagDirection,
+}
+
+// A named area of the sprite, e.g. a hitbox. Only its first key is kept.
+#[derive(Debug)]
+pub struct Slice {
+    pub name: String,
+    pub bounds: Rect,
+}
+
+impl AsepriteFile {
+    pub fn parse(bytes: &[u8]) -> Result<Self, AsepriteLoadError> {
+        let mut header = ByteReader::new(bytes);
+        header.skip(4)?;
+        if header.u16()? != 0xA5E0 {
+            return Err(AsepriteLoadError::NotAseprite);
+        }
+        let frame_count = header.u16()?;
+        if frame_count == 0 {
+            return Err(AsepriteLoadError::NoFrames);
+        }
+        let width = header.u16()? as u32;
+        let height = header.u16()? as u32;
+        if width > MAX_CANVAS_SIZE || height > MAX_CANVAS_SIZE {
+            return Err(AsepriteLoadError::CanvasTooLarge { width, height });
+        }
+        let (_, atlas_width, atlas_height) = atlas_grid(frame_count as u32, width, height);
+        if atlas_width > MAX_ATLAS_SIZE || atlas_height > MAX_ATLAS_SIZE {
+            return Err(AsepriteLoadError::AtlasTooLarge {
+                width: atlas_width,
+                height: atlas_height,
+            });
+        }
+        let color_depth = header.u16()?;
+        if !matches!(color_depth, 8 | 16 | 32) {
+            return Err(AsepriteLoadError::UnsupportedColorDepth(color_depth));
+        }
+        // Layer opacity is only meaningful when this flag is set
+        let layer_opacity_valid = header.u32()? & 1 != 0;
+        header.skip(10)?;
+        let transparent_index = header.u8()?;
+
+        let mut file = AsepriteFile {
+            width,
+            height,
+            layers: Vec::new(),
+            frames: Vec::with_capacity(frame_count as usize),
+            tags: Vec::new(),
+            slices: Vec::new(),
+            color_depth,
+            palette: Vec::new(),
+            transparent_index,
+        };
+
+        let mut reader = ByteReader::new(bytes);
+        reader.skip(128)?;
+
+        for _ in 0..frame_count {
+            let frame_size = reader.u32()? as usize;
+            let mut frame = ByteReader::new(reader.take(frame_size.saturating_sub(4))?);
+            frame.skip(2)?;
+            let old_chunk_count = frame.u16()? as u32;
+            let duration = frame.u16()?;
+            frame.skip(2)?;
+            let chunk_count = match frame.u32()? {
+                0 => old_chunk_count,
+                count => count,
+            };
+
+            let mut cels = Vec::new();
+            for _ in 0..chunk_count {
+                let chunk_size = frame.u32()? as usize;
+                let chunk_type = frame.u16()?;
+                let mut chunk = ByteReader::new(frame.take(chunk_size.saturating_sub(6))?);
+
+                match chunk_type {
+                    0x2004 => file
+                        .layers
+                        .push(parse_layer(&mut chunk, layer_opacity_valid)?),
+                    0x2005 => cels.push(parse_cel(&mut chunk, color_depth)?),
+                    0x2018 => file.tags.extend(parse_tags(&mut chunk)?),
+                    0x2019 => parse_palette(&mut chunk, &mut file.palette)?,
+                    0x2022 => file.slices.push(parse_slice(&mut chunk)?),
+                    // Everything else (old palettes, color profiles, user data, ...) doesn't
+                    // change how the frames look
+                    _ => {}
+                }
+            }
+
+            file.frames.push(Frame {
+                // Aseprite never saves 0ms frames, but a broken file shouldn't hang the animation
+                duration: Duration::from_millis(duration.max(1) as u64),
+                cels,
+            });
+        }
+
+        for tag in &file.tags {
+            if tag.from > tag.to || tag.to >= file.frames.len() {
+                return Err(AsepriteLoadError::TagOutOfRange(tag.name.clone()));
+            }
+        }
+
+        // Follow every link now, so drawing a frame can't fail later
+        for (index, frame) in file.frames.iter().enumerate() {
+            for cel in &frame.cels {
+                file.cel_image(index, cel.layer)?;
+            }
+        }
+
+        Ok(file)
+    }
+
+    // Flattens the visible layers of a frame into RGBA8 pixels, the same way Aseprite would
+    // export it. Blend modes other than normal are drawn as normal.
+    pub fn render_frame(&self, frame: usize) -> Vec<u8> {
+        let mut canvas = vec![0u8; self.width as usize * self.height as usize * 4];
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            if !layer.is_image || !self.is_visible(index) {
+                continue;
+            }
+
+            let Ok(Some((cel, width, height, pixels))) = self.cel_image(frame, index) else {
+                continue;
+            };
+            let opacity = cel.opacity as u32 * layer.opacity as u32 / 255;
+
+            for y in 0..height as i32 {
+                for x in 0..width as i32 {
+                    let (canvas_x, canvas_y) = (cel.x + x, cel.y + y);
+                    if canvas_x < 0
+                        || canvas_y < 0
+                        || canvas_x >= self.width as i32
+                        || canvas_y >= self.height as i32
+                    {
+                        continue;
+                    }
+
+                    let source = self.color(pixels, y as usize * width as usize + x as usize);
+                    let target = (canvas_y as usize * self.width as usize + canvas_x as usize) * 4;
+                    blend(&mut canvas[target..target + 4], source, opacity);
+                }
+            }
+        }
+
+        canvas
+    }
+
+    // Renders every frame into a grid on one texture, with an atlas layout to match.
+    pub fn atlas(&self) -> (Image, TextureAtlasLayout) {
+        let (columns, atlas_width, atlas_height) =
+            atlas_grid(self.frames.len() as u32, self.width, self.height);
+
+        let mut data = vec![0u8; atlas_width as usize * atlas_height as usize * 4];
+        let mut layout =
+            TextureAtlasLayout::new_empty(Vec2::new(atlas_width as f32, atlas_height as f32));
+
+        for frame in 0..self.frames.len() {
+            let left = frame as u32 % columns * self.width;
+            let top = frame as u32 / columns * self.height;
+            let pixels = self.render_frame(frame);
+
+            let row_bytes = self.width as usize * 4;
+            for y in 0..self.height as usize {
+                let source = y * row_bytes;
+                let target = ((top as usize + y) * atlas_width as usize + left as usize) * 4;
+                data[target..target + row_bytes]
+                    .copy_from_slice(&pixels[source..source + row_bytes]);
+            }
+
+            layout.add_texture(Rect::new(
+                left as f32,
+                top as f32,
+                (left + self.width) as f32,
+                (top + self.height) as f32,
+            ));
+        }
+
+        let image = Image::new(
+            Extent3d {
+                width: atlas_width,
+                height: atlas_height,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+
+        (image, layout)
+    }
+
+    // A layer is only drawn if it and every group it's in are visible
+    fn is_visible(&self, index: usize) -> bool {
+        let mut level = self.layers[index].child_level;
+        for layer in self.layers[..=index].iter().rev() {
+            if layer.child_level <= level {
+                if !layer.visible {
+                    return false;
+                }
+                if layer.child_level == 0 {
+                    break;
+                }
+                level = layer.child_level - 1;
+            }
+        }
+        true
+    }
+
+    // The layer's cel in a frame, and the pixels it shows. Linked cels share another frame's
+    // pixels, but keep their own position and opacity.
+    fn cel_image(
+        &self,
+        frame: usize,
+        layer: usize,
+    ) -> Result<Option<(&Cel, u32, u32, &[u8])>, AsepriteLoadError> {
+        let find = |frame: usize| {
+            self.frames
+                .get(frame)?
+                .cels
+                .iter()
+                .find(|cel| cel.layer == layer)
+        };
+
+        let Some(cel) = find(frame) else {
+            return Ok(None);
+        };
+
+        // Every link has to lead to a different frame, so a chain can't be longer than there are
+        // frames unless it loops
+        let mut source = cel;
+        for _ in 0..self.frames.len() {
+            match &source.content {
+                CelContent::Image {
+                    width,
+                    height,
+                    pixels,
+                } => return Ok(Some((cel, *width, *height, pixels))),
+                CelContent::Linked(linked) => {
+                    source = find(*linked).ok_or(AsepriteLoadError::BrokenLink { frame, layer })?;
+                }
+                CelContent::Tilemap => return Ok(None),
+            }
+        }
+
+        Err(AsepriteLoadError::BrokenLink { frame, layer })
+    }
+
+    fn color(&self, pixels: &[u8], index: usize) -> [u8; 4] {
+        match self.color_depth {
+            32 => {
+                let pixel = &pixels[index * 4..index * 4 + 4];
+                [pixel[0], pixel[1], pixel[2], pixel[3]]
+            }
+            16 => {
+                let (value, alpha) = (pixels[index * 2], pixels[index * 2 + 1]);
+                [value, value, value, alpha]
+            }
+            _ => {
+                let entry = pixels[index];
+                if entry == self.transparent_index {
+                    return [0; 4];
+                }
+                self.palette.get(entry as usize).copied().unwrap_or([0; 4])
+            }
+        }
+    }
+}
+
+// How many columns of frames the atlas has, and its width and height in pixels. Frames are laid out
+// in a square-ish grid.
+fn atlas_grid(frames: u32, width: u32, height: u32) -> (u32, u32, u32) {
+    let columns = (frames as f32).sqrt().ceil() as u32;
+    let rows = frames.div_ceil(columns);
+    (columns, columns * width, rows * height)
+}
+
+// Aseprite's normal blend mode, on colors without premultiplied alpha
+fn blend(target: &mut [u8], source: [u8; 4], opacity: u32) {
+    let source_alpha = source[3] as u32 * opacity / 255;
+    if source_alpha == 0 {
+        return;
+    }
+
+    let target_alpha = target[3] as u32;
+    if target_alpha == 0 {
+        target[..3].copy_from_slice(&source[..3]);
+        target[3] = source_alpha as u8;
+        return;
+    }
+
+    let alpha = source_alpha + target_alpha - source_alpha * target_alpha / 255;
+    for channel in 0..3 {
+        let (s, t) = (source[channel] as i32, target[channel] as i32);
+        target[channel] = (t + (s - t) * source_alpha as i32 / alpha as i32) as u8;
+    }
+    target[3] = alpha as u8;
+}
+
+fn parse_layer(chunk: &mut ByteReader, opacity_valid: bool) -> Result<Layer, AsepriteLoadError> {
+    let flags = chunk.u16()?;
+    let layer_type = chunk.u16()?;
+    let child_level = chunk.u16()?;
+    // Default size and blend mode
+    chunk.skip(6)?;
+    let opacity = chunk.u8()?;
+    chunk.skip(3)?;
+
+    Ok(Layer {
+        name: chunk.string()?,
+        visible: flags & 1 != 0,
+        child_level,
+        opacity: if opacity_valid { opacity } else { 255 },
+        is_image: layer_type == 0,
+    })
+}
+
+fn parse_cel(chunk: &mut ByteReader, color_depth: u16) -> Result<Cel, AsepriteLoadError> {
+    let layer = chunk.u16()? as usize;
+    let x = chunk.i16()? as i32;
+    let y = chunk.i16()? as i32;
+    let opacity = chunk.u8()?;
+    let cel_type = chunk.u16()?;
+    // Z-index and reserved bytes
+    chunk.skip(7)?;
+
+    let content = match cel_type {
+        0 | 2 => {
+            let width = chunk.u16()? as u32;
+            let height = chunk.u16()? as u32;
+            // Cels can be bigger than their canvas, but not bigger than any canvas is allowed to be
+            if width > MAX_CANVAS_SIZE || height > MAX_CANVAS_SIZE {
+                return Err(AsepriteLoadError::CelTooLarge);
+            }
+            let size = width as usize * height as usize * color_depth as usize / 8;
+
+            let pixels = if cel_type == 0 {
+                chunk.take(size)?.to_vec()
+            } else {
+                // Never decompress more than the cel needs, whatever the data says
+                let mut pixels = Vec::new();
+                ZlibDecoder::new(chunk.rest())
+                    .take(size as u64)
+                    .read_to_end(&mut pixels)
+                    .map_err(AsepriteLoadError::DecompressError)?;
+                pixels
+            };
+            if pixels.len() < size {
+                return Err(AsepriteLoadError::Truncated);
+            }
+
+            CelContent::Image {
+                width,
+                height,
+                pixels,
+            }
+        }
+        1 => CelContent::Linked(chunk.u16()? as usize),
+        _ => CelContent::Tilemap,
+    };
+
+    Ok(Cel {
+        layer,
+        x,
+        y,
+        opacity,
+        content,
+    })
+}
+
+fn parse_tags(chunk: &mut ByteReader) -> Result<Vec<Tag>, AsepriteLoadError> {
+    let count = chunk.u16()?;
+    chunk.skip(8)?;
+
+    (0..count)
+        .map(|_| {
+            let from = chunk.u16()? as usize;
+            let to = chunk.u16()? as usize;
+            let direction = match chunk.u8()? {
+                1 => TagDirection::Reverse,
+                2 => TagDirection::PingPong,
+                3 => TagDirection::PingPongReverse,
+                _ => TagDirection::Forward,
+            };
+            // Repeat count, reserved bytes and the tag's color
+            chunk.skip(12)?;
+
+            Ok(Tag {
+                name: chunk.string()?,
+                from,
+                to,
+                direction,
+            })
+        })
+        .collect()
+}
+
+fn parse_palette(
+    chunk: &mut ByteReader,
+    palette: &mut Vec<[u8; 4]>,
+) -> Result<(), AsepriteLoadError> {
+    let size = chunk.u32()? as usize;
+    let first = chunk.u32()? as usize;
+    let last = chunk.u32()? as usize;
+    chunk.skip(8)?;
+
+    palette.resize(size.max(palette.len()), [0; 4]);
+    for index in first..=last {
+        let flags = chunk.u16()?;
+        let color = [chunk.u8()?, chunk.u8()?, chunk.u8()?, chunk.u8()?];
+        if flags & 1 != 0 {
+            // The color's name
+            chunk.string()?;
+        }
+        if let Some(entry) = palette.get_mut(index) {
+            *entry = color;
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_slice(chunk: &mut ByteReader) -> Result<Slice, AsepriteLoadError> {
+    let key_count = chunk.u32()?;
+    chunk.skip(8)?;
+    let name = chunk.string()?;
+
+    let bounds = if key_count > 0 {
+        chunk.skip(4)?;
+        let x = chunk.i32()? as f32;
+        let y = chunk.i32()? as f32;
+        let width = chunk.u32()? as f32;
+        let height = chunk.u32()? as f32;
+        Rect::new(x, y, x + width, y + height)
+    } else {
+        Rect::default()
+    };
+
+    Ok(Slice { name, bounds })
+}
+
+// Reads the little-endian values the format is made of
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], AsepriteLoadError> {
+        if count > self.bytes.len() {
+            return Err(AsepriteLoadError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), AsepriteLoadError> {
+        self.take(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, AsepriteLoadError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, AsepriteLoadError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn i16(&mut self) -> Result<i16, AsepriteLoadError> {
+        Ok(i16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, AsepriteLoadError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i32(&mut self) -> Result<i32, AsepriteLoadError> {
+        Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn string(&mut self) -> Result<String, AsepriteLoadError> {
+        let length = self.u16()? as usize;
+        Ok(String::from_utf8_lossy(self.take(length)?).into_owned())
+    }
+}
@@ -1,3 +1,5 @@
+use std::{fs, path::Path};
+
 use bevy::{
     asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
     ecs::system::SystemParam,
@@ -11,6 +13,7 @@ use bevy_asset_loader::{
         LoadingStateAppExt,
     },
 };
+use schemars::{gen::SchemaGenerator, schema::Schema, schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 
 use crate::GameState;
@@ -35,16 +38,116 @@ pub struct GameAssetCollection {
 }
 
 // This is our serializable config file, which we're making as a custom asset.
-#[derive(Default, Debug, Clone, Serialize, Deserialize, TypePath, Asset)]
+// The `///` comments on its fields end up as descriptions in the JSON Schema editors use for it.
+#[derive(Default, Debug, Clone, Serialize, Deserialize, JsonSchema, TypePath, Asset)]
 #[serde(deny_unknown_fields)]
 pub struct GameConfigFile {
+    /// Where editors can find the schema for this file
+    #[serde(rename = "$schema", default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+    /// How fast the goose walks, in pixels per second
+    #[schemars(schema_with = "positive::<f32>")]
     pub player_move_speed: f32,
+    /// How far from the centre flowers can be scattered in each direction, in pixels
+    #[schemars(schema_with = "positive::<f32>")]
     pub world_size: f32,
+    /// How many flowers are scattered at the start of each game
     pub num_flowers: u32,
+    /// How close the goose has to get to a flower to pick it, in pixels
+    #[schemars(range(min = 0))]
     pub flower_pickup_range: f32,
+    /// How long each game lasts, in seconds
+    #[schemars(schema_with = "positive::<f32>")]
     pub game_time_seconds: f32,
+    /// How many times per second gameplay is simulated, independent of the frame rate
+    #[serde(default = "default_simulation_hz")]
+    #[schemars(schema_with = "positive::<f64>")]
+    pub simulation_hz: f64,
+}
+
+fn default_simulation_hz() -> f64 {
+    64.
+}
+
+// Matches the `must be greater than 0` checks in `GameConfigFile::validate`
+fn positive<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = T::json_schema(gen).into_object();
+    schema.number().exclusive_minimum = Some(0.);
+    schema.into()
+}
+
+impl GameConfigFile {
+    // Reads a config straight from disk, for when there's no asset server to load it with.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, GameConfigLoadError> {
+        let bytes = fs::read(path)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    // The JSON Schema for config files, as shipped in `assets/game_config.schema.json`.
+    pub fn schema_json() -> String {
+        let schema = serde_json::to_string_pretty(&schema_for!(GameConfigFile))
+            .expect("schemas always serialize");
+        schema + "\n"
+    }
+
+    // Checks for values that parse fine but don't make a playable game.
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let mut check = |ok: bool, field: &'static str, message: &str| {
+            if !ok {
+                problems.push(ConfigProblem {
+                    field,
+                    message: message.to_string(),
+                });
+            }
+        };
+
+        check(
+            self.player_move_speed.is_finite() && self.player_move_speed > 0.,
+            "player_move_speed",
+            "must be greater than 0",
+        );
+        check(
+            self.world_size.is_finite() && self.world_size > 0.,
+            "world_size",
+            "must be greater than 0",
+        );
+        check(
+            self.flower_pickup_range.is_finite() && self.flower_pickup_range >= 0.,
+            "flower_pickup_range",
+            "must not be negative",
+        );
+        check(
+            self.game_time_seconds.is_finite() && self.game_time_seconds > 0.,
+            "game_time_seconds",
+            "must be greater than 0",
+        );
+        check(
+            self.simulation_hz.is_finite() && self.simulation_hz > 0.,
+            "simulation_hz",
+            "must be greater than 0",
+        );
+
+        problems
+    }
+}
+
+// A config value that's out of range, from `GameConfigFile::validate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigProblem {
+    pub field: &'static str,
+    pub message: String,
 }
 
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.field, self.message)
+    }
+}
+
+// The file extensions a config can be loaded from
+pub const CONFIG_EXTENSIONS: &[&str] = &["json"];
+
 // This simplifies loading the config data into a system
 #[derive(SystemParam)]
 pub struct GameConfig<'w> {
@@ -87,7 +190,12 @@ impl AssetLoader for GameConfigLoader {
             reader.read_to_end(&mut bytes).await?;
 
             match serde_json::from_slice::<GameConfigFile>(&bytes) {
-                Ok(cfg) => Ok(cfg),
+                Ok(cfg) => {
+                    for problem in cfg.validate() {
+                        warn!("Game config problem: {}", problem);
+                    }
+                    Ok(cfg)
+                }
                 Err(err) => {
                     error!("Unable to parse game config: {:?}", err);
                     Err(err.into())
@@ -97,6 +205,6 @@ impl AssetLoader for GameConfigLoader {
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        CONFIG_EXTENSIONS
     }
 }
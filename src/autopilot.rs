@@ -0,0 +1,226 @@
+use std::str::FromStr;
+
+use bevy::prelude::*;
+
+use crate::{
+    enemy::Enemy,
+    input::{InputSet, InputSource, InputState},
+    player::{FlowerCount, Player},
+    GameState,
+};
+
+// Plays the game by writing into `InputState`, the same way the replay does, so runs can be
+// automated and strategies compared. It does nothing until `Autopilot::enable` is called.
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlannedRoute>()
+            .add_systems(OnEnter(GameState::Gaming), clear_route)
+            .add_systems(OnEnter(GameState::GameOver), report_score)
+            .add_systems(
+                FixedUpdate,
+                steer
+                    .in_set(InputSet::ReadInput)
+                    .run_if(resource_equals(InputSource::Autopilot))
+                    .run_if(in_state(GameState::Gaming)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    // Always heads for whichever flower is closest right now
+    NearestFlower,
+    // Plans a short path through every flower up front, then follows it
+    PlannedRoute,
+}
+
+impl Strategy {
+    // Picks the strategy from `--autopilot <nearest|route>` on the command line. Fails if the
+    // strategy is missing or isn't one of those.
+    pub fn from_args() -> Result<Option<Self>, String> {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg != "--autopilot" {
+                continue;
+            }
+
+            return match args.next() {
+                Some(name) => name.parse().map(Some),
+                None => Err("--autopilot needs a strategy: 'nearest' or 'route'".to_string()),
+            };
+        }
+        Ok(None)
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nearest" => Ok(Strategy::NearestFlower),
+            "route" => Ok(Strategy::PlannedRoute),
+            _ => Err(format!(
+                "unknown autopilot strategy '{}'; expected 'nearest' or 'route'",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct Autopilot(pub Strategy);
+
+impl Autopilot {
+    // Hands control of the player over to the autopilot.
+    pub fn enable(world: &mut World, strategy: Strategy) {
+        world.insert_resource(Autopilot(strategy));
+        world.insert_resource(InputSource::Autopilot);
+    }
+}
+
+// The flowers left to visit, in order, for `Strategy::PlannedRoute`
+#[derive(Default, Debug, Resource)]
+struct PlannedRoute {
+    flowers: Vec<Entity>,
+    planned: bool,
+}
+
+fn clear_route(mut route: ResMut<PlannedRoute>) {
+    route.flowers.clear();
+    route.planned = false;
+}
+
+fn steer(
+    autopilot: Option<Res<Autopilot>>,
+    mut route: ResMut<PlannedRoute>,
+    player: Query<&Transform, With<Player>>,
+    flowers: Query<(Entity, &Transform), With<Enemy>>,
+    mut input: ResMut<InputState>,
+) {
+    let Some(autopilot) = autopilot else {
+        return;
+    };
+
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let position = player.translation.truncate();
+
+    let target = match autopilot.0 {
+        Strategy::NearestFlower => flowers
+            .iter()
+            .map(|(_, transform)| transform.translation.truncate())
+            .min_by(|a, b| {
+                a.distance_squared(position)
+                    .total_cmp(&b.distance_squared(position))
+            }),
+        Strategy::PlannedRoute => {
+            if !route.planned {
+                let (entities, points): (Vec<_>, Vec<_>) = flowers
+                    .iter()
+                    .map(|(entity, transform)| (entity, transform.translation.truncate()))
+                    .unzip();
+
+                route.flowers = plan_route(position, &points)
+                    .into_iter()
+                    .map(|i| entities[i])
+                    .collect();
+                route.planned = true;
+            }
+
+            // Skip anything that's been picked since, on purpose or on the way past
+            while let Some(next) = route.flowers.first() {
+                if flowers.contains(*next) {
+                    break;
+                }
+                route.flowers.remove(0);
+            }
+
+            route
+                .flowers
+                .first()
+                .and_then(|next| flowers.get(*next).ok())
+                .map(|(_, transform)| transform.translation.truncate())
+        }
+    };
+
+    match target {
+        Some(target) => input.set_target(target),
+        None => input.clear_target(),
+    }
+}
+
+// Orders the points into a short path starting from `start`: a nearest-neighbour tour, tidied up
+// with 2-opt until it stops improving. Returns indices into `points`.
+pub fn plan_route(start: Vec2, points: &[Vec2]) -> Vec<usize> {
+    let mut remaining = (0..points.len()).collect::<Vec<_>>();
+    let mut route = Vec::with_capacity(points.len());
+    let mut current = start;
+
+    while !remaining.is_empty() {
+        let (index, _) = remaining
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| {
+                points[**a]
+                    .distance_squared(current)
+                    .total_cmp(&points[**b].distance_squared(current))
+            })
+            .expect("remaining is not empty");
+        let next = remaining.swap_remove(index);
+        current = points[next];
+        route.push(next);
+    }
+
+    // The path starts at the player, which can't be moved, so keep it at the front.
+    let mut path = std::iter::once(start)
+        .chain(route.iter().map(|i| points[*i]))
+        .collect::<Vec<_>>();
+    let len = path.len();
+
+    const MAX_PASSES: usize = 8;
+    for _ in 0..MAX_PASSES {
+        let mut improved = false;
+
+        for i in 1..len - 1 {
+            for j in i + 1..len {
+                // Reversing i..=j swaps the edges on either side of it; the path is open, so the
+                // last point has no edge after it.
+                let (before, after) = if j + 1 < len {
+                    (
+                        path[i - 1].distance(path[i]) + path[j].distance(path[j + 1]),
+                        path[i - 1].distance(path[j]) + path[i].distance(path[j + 1]),
+                    )
+                } else {
+                    (path[i - 1].distance(path[i]), path[i - 1].distance(path[j]))
+                };
+
+                if after + f32::EPSILON < before {
+                    path[i..=j].reverse();
+                    route[i - 1..j].reverse();
+                    improved = true;
+                }
+            }
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    route
+}
+
+fn report_score(autopilot: Option<Res<Autopilot>>, player: Query<&FlowerCount, With<Player>>) {
+    let (Some(autopilot), Ok(count)) = (autopilot, player.get_single()) else {
+        return;
+    };
+
+    info!(
+        "Autopilot ({:?}) collected {} flowers",
+        autopilot.0, count.0
+    );
+}
@@ -0,0 +1,283 @@
+// Plays many headless games with the autopilot across a grid of config values, and reports how
+// the scores are distributed for each combination.
+//
+//   super-waddle-balance --speeds 150,250,350 --ranges 8,16 --flowers 250,500 --times 10,20 \
+//       --runs 200 --strategy nearest --output balance.csv
+//
+// Anything not given on the command line comes from `--base` (`assets/game_config.json` by
+// default). The report is JSON if `--output` ends in `.json`, and CSV otherwise.
+
+use std::{fs, path::PathBuf, process};
+
+use serde::Serialize;
+use super_waddle::{
+    assets::GameConfigFile, autopilot::Strategy, game::SeedOverride, headless::HeadlessApp,
+    GameState,
+};
+
+struct Args {
+    base: PathBuf,
+    output: PathBuf,
+    runs: u32,
+    strategy: Strategy,
+    speeds: Option<Vec<f32>>,
+    ranges: Option<Vec<f32>>,
+    flowers: Option<Vec<u32>>,
+    times: Option<Vec<f32>>,
+}
+
+impl Args {
+    fn parse() -> Result<Self, String> {
+        let mut args = Args {
+            base: PathBuf::from("assets/game_config.json"),
+            output: PathBuf::from("balance.csv"),
+            runs: 100,
+            strategy: Strategy::NearestFlower,
+            speeds: None,
+            ranges: None,
+            flowers: None,
+            times: None,
+        };
+
+        let mut argv = std::env::args().skip(1);
+        while let Some(flag) = argv.next() {
+            let value = argv
+                .next()
+                .ok_or_else(|| format!("{} needs a value", flag))?;
+
+            match flag.as_str() {
+                "--base" => args.base = value.into(),
+                "--output" => args.output = value.into(),
+                "--runs" => args.runs = parse_value(&flag, &value)?,
+                "--strategy" => args.strategy = value.parse()?,
+                "--speeds" => args.speeds = Some(parse_list(&flag, &value)?),
+                "--ranges" => args.ranges = Some(parse_list(&flag, &value)?),
+                "--flowers" => args.flowers = Some(parse_list(&flag, &value)?),
+                "--times" => args.times = Some(parse_list(&flag, &value)?),
+                _ => return Err(format!("unknown argument {}", flag)),
+            }
+        }
+
+        Ok(args)
+    }
+
+    // Every combination of the values given, with the rest of the config taken from `base`.
+    fn grid(&self, base: &GameConfigFile) -> Vec<GameConfigFile> {
+        let speeds = self.speeds.clone().unwrap_or(vec![base.player_move_speed]);
+        let ranges = self
+            .ranges
+            .clone()
+            .unwrap_or(vec![base.flower_pickup_range]);
+        let flowers = self.flowers.clone().unwrap_or(vec![base.num_flowers]);
+        let times = self.times.clone().unwrap_or(vec![base.game_time_seconds]);
+
+        let mut grid = Vec::new();
+        for &player_move_speed in &speeds {
+            for &flower_pickup_range in &ranges {
+                for &num_flowers in &flowers {
+                    for &game_time_seconds in &times {
+                        grid.push(GameConfigFile {
+                            player_move_speed,
+                            flower_pickup_range,
+                            num_flowers,
+                            game_time_seconds,
+                            ..base.clone()
+                        });
+                    }
+                }
+            }
+        }
+        grid
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} got an invalid value '{}'", flag, value))
+}
+
+fn parse_list<T: std::str::FromStr>(flag: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|item| parse_value(flag, item.trim()))
+        .collect()
+}
+
+// How the scores for one combination of config values were spread out
+#[derive(Debug, Serialize)]
+struct Report {
+    player_move_speed: f32,
+    flower_pickup_range: f32,
+    num_flowers: u32,
+    game_time_seconds: f32,
+    runs: usize,
+    min: i32,
+    p10: i32,
+    median: i32,
+    p90: i32,
+    max: i32,
+    mean: f64,
+    std_dev: f64,
+    // Share of the flowers collected on average, to spot combinations that are trivially easy
+    mean_completion: f64,
+}
+
+impl Report {
+    fn new(config: &GameConfigFile, mut scores: Vec<i32>) -> Self {
+        scores.sort_unstable();
+
+        let runs = scores.len();
+        let percentile = |p: f64| scores[((runs - 1) as f64 * p).round() as usize];
+        let mean = scores.iter().map(|s| *s as f64).sum::<f64>() / runs as f64;
+        let variance = scores
+            .iter()
+            .map(|s| (*s as f64 - mean).powi(2))
+            .sum::<f64>()
+            / runs as f64;
+
+        Self {
+            player_move_speed: config.player_move_speed,
+            flower_pickup_range: config.flower_pickup_range,
+            num_flowers: config.num_flowers,
+            game_time_seconds: config.game_time_seconds,
+            runs,
+            min: scores[0],
+            p10: percentile(0.1),
+            median: percentile(0.5),
+            p90: percentile(0.9),
+            max: scores[runs - 1],
+            mean,
+            std_dev: variance.sqrt(),
+            mean_completion: if config.num_flowers > 0 {
+                mean / config.num_flowers as f64
+            } else {
+                0.
+            },
+        }
+    }
+}
+
+// Plays one game to the end and returns the score. Each run number gets its own flower layout,
+// which is the same for every combination so they're compared on equal terms.
+fn play(config: &GameConfigFile, strategy: Strategy, run: u32) -> i32 {
+    let mut app = HeadlessApp::new(config.clone());
+    app.world_mut()
+        .insert_resource(SeedOverride(Some(run as u64)));
+    app.enable_autopilot(strategy);
+
+    // A little slack past the timer in case the last tick lands late
+    let max_frames = ((config.game_time_seconds as f64 + 1.) * config.simulation_hz).ceil() as u32;
+    for _ in 0..max_frames {
+        app.step(1);
+        if app.state() == GameState::GameOver {
+            break;
+        }
+    }
+
+    app.flower_count().unwrap_or_default()
+}
+
+fn write_csv(reports: &[Report]) -> String {
+    let mut csv = String::from(
+        "player_move_speed,flower_pickup_range,num_flowers,game_time_seconds,runs,\
+         min,p10,median,p90,max,mean,std_dev,mean_completion\n",
+    );
+    for r in reports {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{},{},{},{:.2},{:.2},{:.3}\n",
+            r.player_move_speed,
+            r.flower_pickup_range,
+            r.num_flowers,
+            r.game_time_seconds,
+            r.runs,
+            r.min,
+            r.p10,
+            r.median,
+            r.p90,
+            r.max,
+            r.mean,
+            r.std_dev,
+            r.mean_completion
+        ));
+    }
+    csv
+}
+
+fn main() {
+    let args = Args::parse().unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        process::exit(2);
+    });
+
+    let base = GameConfigFile::from_file(&args.base).unwrap_or_else(|err| {
+        eprintln!("Unable to load {}: {:?}", args.base.display(), err);
+        process::exit(1);
+    });
+
+    if args.runs == 0 {
+        eprintln!("--runs must be at least 1");
+        process::exit(2);
+    }
+
+    let grid = args.grid(&base);
+
+    // Checked up front so a bad value doesn't stop the sweep partway, or make `play` run forever
+    let mut invalid = false;
+    for config in &grid {
+        for problem in config.validate() {
+            eprintln!(
+                "speed {} range {} flowers {} time {}s: {}",
+                config.player_move_speed,
+                config.flower_pickup_range,
+                config.num_flowers,
+                config.game_time_seconds,
+                problem
+            );
+            invalid = true;
+        }
+    }
+    if invalid {
+        process::exit(2);
+    }
+
+    let mut reports = Vec::with_capacity(grid.len());
+
+    for (i, config) in grid.iter().enumerate() {
+        let scores = (0..args.runs)
+            .map(|run| play(config, args.strategy, run))
+            .collect::<Vec<_>>();
+        let report = Report::new(config, scores);
+
+        println!(
+            "[{}/{}] speed {} range {} flowers {} time {}s: median {} (p10 {}, p90 {})",
+            i + 1,
+            grid.len(),
+            report.player_move_speed,
+            report.flower_pickup_range,
+            report.num_flowers,
+            report.game_time_seconds,
+            report.median,
+            report.p10,
+            report.p90
+        );
+        reports.push(report);
+    }
+
+    let is_json = args
+        .output
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    let contents = if is_json {
+        serde_json::to_string_pretty(&reports).expect("reports serialize to json")
+    } else {
+        write_csv(&reports)
+    };
+
+    if let Err(err) = fs::write(&args.output, contents) {
+        eprintln!("Unable to write {}: {:?}", args.output.display(), err);
+        process::exit(1);
+    }
+
+    println!("Wrote {}", args.output.display());
+}
@@ -0,0 +1,136 @@
+// Checks game config files without starting the game, so mistakes show up in the terminal (or CI)
+// instead of as a config that silently fails to load.
+//
+//   super-waddle-config check assets/game_config.json other.json
+//   super-waddle-config print assets/game_config.json
+//   super-waddle-config schema > assets/game_config.schema.json
+//
+// `check` reports parse errors and out-of-range values with their line and column, `print` shows
+// the config the game would actually use once defaults are filled in, and `schema` writes out a
+// JSON Schema for editors to autocomplete and validate against.
+
+use std::{fs, path::Path, process};
+
+use super_waddle::assets::{GameConfigFile, CONFIG_EXTENSIONS};
+
+const USAGE: &str = "usage: super-waddle-config <check <file>... | print <file> | schema>";
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let Some((command, files)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    };
+
+    let ok = match (command.as_str(), files) {
+        ("check", [_, ..]) => {
+            // Every file is checked, so all their problems are reported at once
+            let failed = files
+                .iter()
+                .filter(|file| check(Path::new(file)).is_none())
+                .count();
+            failed == 0
+        }
+        ("print", [file]) => match check(Path::new(file)) {
+            Some(config) => {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&config).expect("configs always serialize")
+                );
+                true
+            }
+            None => false,
+        },
+        ("schema", []) => {
+            print!("{}", GameConfigFile::schema_json());
+            true
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+
+    if !ok {
+        process::exit(1);
+    }
+}
+
+// Loads and validates a config, printing every problem found. Only returns configs that are
+// entirely valid.
+fn check(path: &Path) -> Option<GameConfigFile> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) => {
+            eprintln!("{}: error: {}", path.display(), err);
+            return None;
+        }
+    };
+
+    let config = match parse(path, &text) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return None;
+        }
+    };
+
+    let problems = config.validate();
+    for problem in &problems {
+        match locate(&text, problem.field) {
+            Some((line, column)) => {
+                eprintln!("{}:{}:{}: error: {}", path.display(), line, column, problem)
+            }
+            // Not in the file, so it came from a default
+            None => eprintln!("{}: error: {} (default)", path.display(), problem),
+        }
+    }
+
+    if problems.is_empty() {
+        eprintln!("{}: ok", path.display());
+        Some(config)
+    } else {
+        None
+    }
+}
+
+// Parses the config according to its extension, in any format the game can load.
+fn parse(path: &Path, text: &str) -> Result<GameConfigFile, String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default();
+
+    match extension {
+        "json" => serde_json::from_str(text).map_err(|err| {
+            // serde_json puts the position at the end of its message; move it to the front
+            let message = err.to_string();
+            let message = message
+                .strip_suffix(&format!(" at line {} column {}", err.line(), err.column()))
+                .unwrap_or(&message);
+            format!(
+                "{}:{}:{}: error: {}",
+                path.display(),
+                err.line(),
+                err.column(),
+                message
+            )
+        }),
+        _ => Err(format!(
+            "{}: error: unsupported format '{}'; expected one of: {}",
+            path.display(),
+            extension,
+            CONFIG_EXTENSIONS.join(", ")
+        )),
+    }
+}
+
+// Finds the 1-based line and column of a field's key in the source text.
+fn locate(text: &str, field: &str) -> Option<(usize, usize)> {
+    let key = format!("\"{}\"", field);
+    text.lines().enumerate().find_map(|(line, contents)| {
+        contents
+            .find(&key)
+            .map(|column| (line + 1, contents[..column].chars().count() + 1))
+    })
+}
@@ -1,17 +1,176 @@
-use bevy::prelude::*;
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    render::camera::{ScalingMode, Viewport},
+    transform::TransformSystem,
+    window::PrimaryWindow,
+};
 
-use crate::player::Player;
+use crate::{enemy::PickFlower, settings::SettingsState, GameState};
 
 pub struct CameraPlugin;
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, init_camera);
+        app.init_resource::<PixelCamera>()
+            .init_resource::<ScreenShake>()
+            .add_systems(Startup, init_camera)
+            // The shake is taken off before anything else gets to move the camera, and put back
+            // on just before the camera's transform is used, so it never builds up.
+            .add_systems(First, remove_camera_shake)
+            .add_systems(
+                Update,
+                (
+                    // Keys pressed on the settings screen are for rebinding
+                    zoom_camera.run_if(in_state(SettingsState::Closed)),
+                    fit_camera_to_window,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, shake_on_pickup.run_if(in_state(GameState::Gaming)))
+            .add_systems(OnEnter(GameState::GameOver), shake_on_time_up)
+            .add_systems(
+                PostUpdate,
+                apply_camera_shake.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+// The zoom keys aren't rebindable, so no action can be bound to them either
+pub const ZOOM_IN_KEYS: [KeyCode; 2] = [KeyCode::Equal, KeyCode::NumpadAdd];
+pub const ZOOM_OUT_KEYS: [KeyCode; 2] = [KeyCode::Minus, KeyCode::NumpadSubtract];
+pub const RESET_ZOOM_KEYS: [KeyCode; 2] = [KeyCode::Digit0, KeyCode::Numpad0];
+
+// Marker component for the camera that looks at the game world
+#[derive(Default, Component)]
+pub struct MainCamera;
+
+// How the main camera fits the game to the window, and how far the player can zoom it
+#[derive(Debug, Clone, Resource)]
+pub struct PixelCamera {
+    // Draws the game at `resolution`, scaled up by a whole number so every pixel of a sprite covers
+    // the same number of screen pixels. The rest of the window is left as bars in the clear color.
+    // Otherwise the game fills the window at whatever size it is.
+    pub pixel_perfect: bool,
+    pub resolution: UVec2,
+    // How far the player can zoom, where 2 makes everything twice as big
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+    // How much each press of a zoom key or notch of the mouse wheel zooms by, when not pixel
+    // perfect. When it is, each step is one more or fewer screen pixels per pixel.
+    pub zoom_step: f32,
+}
+
+impl Default for PixelCamera {
+    fn default() -> Self {
+        Self {
+            pixel_perfect: true,
+            resolution: UVec2::new(1280, 720),
+            min_zoom: 0.5,
+            max_zoom: 4.,
+            zoom_step: 1.25,
+        }
+    }
+}
+
+// How far the player has zoomed the camera it's on
+#[derive(Debug, Component)]
+pub struct CameraZoom {
+    level: f32,
+    // How many screen pixels each pixel of the resolution covers, while pixel perfect
+    window_scale: u32,
+    // What the camera was last fitted to: the window size, zoom level and settings
+    fitted: Option<(UVec2, f32, bool, UVec2)>,
+}
+
+impl Default for CameraZoom {
+    fn default() -> Self {
+        Self {
+            level: 1.,
+            window_scale: 1,
+            fitted: None,
+        }
+    }
+}
+
+impl CameraZoom {
+    pub fn level(&self) -> f32 {
+        self.level
+    }
+}
+
+// How hard the camera shakes and punches when something happens. The settings screen toggles
+// `enabled`.
+#[derive(Debug, Clone, Resource)]
+pub struct ScreenShake {
+    // Turns shaking and punch zoom off entirely, for players who find them uncomfortable
+    pub enabled: bool,
+    // Scales every shake and punch, 1 is as designed
+    pub intensity: f32,
+    // How far the camera moves, in pixels, and turns, in radians, at full trauma
+    pub max_offset: f32,
+    pub max_angle: f32,
+    // How quickly the camera wobbles while shaking
+    pub frequency: f32,
+    // How much trauma wears off per second
+    pub decay: f32,
+    // How far in a full punch zooms (0.1 is 10%), and how much of it wears off per second
+    pub punch_zoom: f32,
+    pub punch_decay: f32,
+}
+
+impl Default for ScreenShake {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 1.,
+            max_offset: 24.,
+            max_angle: 0.05,
+            frequency: 25.,
+            decay: 1.5,
+            punch_zoom: 0.08,
+            punch_decay: 4.,
+        }
+    }
+}
+
+// Shakes the camera it's on. Trauma goes from 0 to 1 and the shake grows with its square, so small
+// knocks barely register while big ones stack up into a proper jolt.
+#[derive(Default, Debug, Component)]
+pub struct CameraShake {
+    trauma: f32,
+    punch: f32,
+    elapsed: f32,
+    // What was added to the transform this frame, so it can be taken off again
+    offset: Vec2,
+    angle: f32,
+    zoom: f32,
+}
+
+impl CameraShake {
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).clamp(0., 1.);
+    }
+
+    // Zooms in briefly, which then springs back out
+    pub fn punch(&mut self, amount: f32) {
+        self.punch = (self.punch + amount).clamp(0., 1.);
+    }
+
+    pub fn trauma(&self) -> f32 {
+        self.trauma
+    }
+
+    fn is_applied(&self) -> bool {
+        self.offset != Vec2::ZERO || self.angle != 0. || self.zoom != 0.
     }
 }
 
 fn init_camera(mut commands: Commands) {
     commands.spawn((
+        MainCamera,
+        CameraZoom::default(),
+        CameraShake::default(),
         Camera2dBundle {
             camera: Camera {
                 clear_color: Color::rgb(0.3f32, 0.3f32, 0.3f32).into(),
@@ -22,3 +181,182 @@ fn init_camera(mut commands: Commands) {
         VisibilityBundle::default(),
     ));
 }
+
+fn zoom_camera(
+    settings: Res<PixelCamera>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut wheel: EventReader<MouseWheel>,
+    mut cameras: Query<&mut CameraZoom, With<MainCamera>>,
+) {
+    // Trackpads scroll by pixels rather than lines, so count every so many as one notch
+    let mut steps = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 100.,
+        })
+        .sum::<f32>();
+    if keyboard.any_just_pressed(ZOOM_IN_KEYS) {
+        steps += 1.;
+    }
+    if keyboard.any_just_pressed(ZOOM_OUT_KEYS) {
+        steps -= 1.;
+    }
+    let reset = keyboard.any_just_pressed(RESET_ZOOM_KEYS);
+
+    if steps == 0. && !reset {
+        return;
+    }
+
+    for mut zoom in &mut cameras {
+        if reset {
+            zoom.level = 1f32.clamp(settings.min_zoom, settings.max_zoom);
+        } else if settings.pixel_perfect {
+            // Only whole numbers of screen pixels per pixel can be shown, and never fewer than one,
+            // so the limits are rounded inwards to those. Otherwise a level that gets drawn the
+            // same as the one next to it would take a step that doesn't seem to do anything.
+            let scale = zoom.window_scale as f32;
+            let fewest = (settings.min_zoom * scale).ceil().max(1.);
+            let most = (settings.max_zoom * scale).floor().max(fewest);
+            let pixels = (zoom.level * scale).round() + steps.round();
+            zoom.level = pixels.clamp(fewest, most) / scale;
+        } else {
+            let level = zoom.level * settings.zoom_step.powf(steps);
+            zoom.level = level.clamp(settings.min_zoom, settings.max_zoom);
+        }
+    }
+}
+
+fn fit_camera_to_window(
+    settings: Res<PixelCamera>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<
+        (&mut Camera, &mut OrthographicProjection, &mut CameraZoom),
+        With<MainCamera>,
+    >,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    // Nothing to fit to while minimised
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    if window_size.min_element() == 0 {
+        return;
+    }
+
+    let resolution = settings.resolution.max(UVec2::ONE);
+    let window_scale = (window_size / resolution).min_element();
+
+    for (mut camera, mut projection, mut zoom) in &mut cameras {
+        let fitted = Some((window_size, zoom.level, settings.pixel_perfect, resolution));
+        if zoom.fitted == fitted {
+            continue;
+        }
+        zoom.fitted = fitted;
+
+        // A window smaller than the resolution can't be pixel perfect, so it just gets squashed
+        if settings.pixel_perfect && window_scale > 0 {
+            let size = resolution * window_scale;
+            camera.viewport = Some(Viewport {
+                physical_position: (window_size - size) / 2,
+                physical_size: size,
+                ..default()
+            });
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: resolution.x as f32,
+                height: resolution.y as f32,
+            };
+
+            // Only zoom to whole numbers of screen pixels per pixel
+            let scale = window_scale as f32;
+            projection.scale = scale / (zoom.level * scale).round().max(1.);
+            zoom.window_scale = window_scale;
+        } else if settings.pixel_perfect {
+            camera.viewport = None;
+            projection.scaling_mode = ScalingMode::Fixed {
+                width: resolution.x as f32,
+                height: resolution.y as f32,
+            };
+            projection.scale = 1. / zoom.level;
+            zoom.window_scale = 1;
+        } else {
+            camera.viewport = None;
+            projection.scaling_mode = ScalingMode::WindowSize(1.);
+            projection.scale = 1. / zoom.level;
+        }
+    }
+}
+
+fn shake_on_pickup(
+    mut events: EventReader<PickFlower>,
+    mut cameras: Query<&mut CameraShake, With<MainCamera>>,
+) {
+    for _ in events.read() {
+        for mut shake in &mut cameras {
+            shake.add_trauma(0.25);
+            shake.punch(0.3);
+        }
+    }
+}
+
+fn shake_on_time_up(mut cameras: Query<&mut CameraShake, With<MainCamera>>) {
+    for mut shake in &mut cameras {
+        shake.add_trauma(0.7);
+        shake.punch(1.);
+    }
+}
+
+fn remove_camera_shake(mut cameras: Query<(&mut CameraShake, &mut Transform)>) {
+    for (mut shake, mut transform) in &mut cameras {
+        if !shake.is_applied() {
+            continue;
+        }
+
+        transform.translation -= shake.offset.extend(0.);
+        transform.rotate_z(-shake.angle);
+        transform.scale /= 1. - shake.zoom;
+
+        shake.offset = Vec2::ZERO;
+        shake.angle = 0.;
+        shake.zoom = 0.;
+    }
+}
+
+fn apply_camera_shake(
+    time: Res<Time>,
+    settings: Res<ScreenShake>,
+    mut cameras: Query<(&mut CameraShake, &mut Transform)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (mut shake, mut transform) in &mut cameras {
+        // Trauma wears off even while shaking is turned off, so turning it back on doesn't
+        // release everything that built up in the meantime.
+        shake.trauma = (shake.trauma - settings.decay * delta).max(0.);
+        shake.punch = (shake.punch - settings.punch_decay * delta).max(0.);
+        if !settings.enabled || (shake.trauma == 0. && shake.punch == 0.) {
+            continue;
+        }
+
+        shake.elapsed += delta;
+        let t = shake.elapsed * settings.frequency;
+        let amount = shake.trauma * shake.trauma * settings.intensity;
+
+        shake.offset = Vec2::new(wobble(t, 0.), wobble(t, 1.)) * settings.max_offset * amount;
+        shake.angle = wobble(t, 2.) * settings.max_angle * amount;
+        // Never zoom in so far the scale could get near zero
+        shake.zoom =
+            (shake.punch * shake.punch * settings.punch_zoom * settings.intensity).clamp(0., 0.5);
+
+        transform.translation += shake.offset.extend(0.);
+        transform.rotate_z(shake.angle);
+        transform.scale *= 1. - shake.zoom;
+    }
+}
+
+// Smooth, irregular movement between -1 and 1, different for each `seed`. Two sines at unrelated
+// frequencies don't visibly repeat over the length of a shake.
+fn wobble(t: f32, seed: f32) -> f32 {
+    (t + seed * 17.).sin() * 0.6 + (t * 2.3 + seed * 31.).sin() * 0.4
+}
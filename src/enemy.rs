@@ -1,4 +1,4 @@
-use std::f32::consts::TAU;
+use std::{f32::consts::TAU, time::Duration};
 
 use bevy::prelude::*;
 use bevy_asset_loader::{
@@ -8,9 +8,16 @@ use bevy_asset_loader::{
         LoadingStateAppExt,
     },
 };
-use rand::{thread_rng, Rng};
-
-use crate::{assets::GameConfig, game::GameObject, player::Player, GameState};
+use rand::Rng;
+
+use crate::{
+    animation::{DespawnWhenFinished, SpriteAnimation, SpriteSheet},
+    assets::GameConfig,
+    game::{GameObject, GameRng, GameSet},
+    player::{Player, PreviousPosition},
+    tween::{Animator, Ease, ScaleLens, Tween},
+    GameState,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
 pub enum EnemySet {
@@ -25,9 +32,12 @@ impl Plugin for EnemyPlugin {
             .configure_loading_state(
                 LoadingStateConfig::new(GameState::Loading).load_collection::<EnemyAssets>(),
             )
-            .add_systems(OnEnter(GameState::Gaming), init_flowers)
             .add_systems(
-                Update,
+                OnEnter(GameState::Gaming),
+                init_flowers.after(GameSet::SeedRng),
+            )
+            .add_systems(
+                FixedUpdate,
                 (detect_proximity, despawn_flower_when_picked)
                     .chain()
                     .in_set(EnemySet::Collisions)
@@ -39,19 +49,37 @@ impl Plugin for EnemyPlugin {
 #[derive(Default, Component)]
 pub struct Enemy;
 
+// Which flower an enemy is. There's only the spike flower for now, but anything that draws flowers
+// differently by kind, like the minimap, goes through this.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub enum FlowerKind {
+    #[default]
+    Spike,
+}
+
+// The stand-in left behind by a picked flower, while its pickup animation plays
+#[derive(Default, Component)]
+pub struct PickedFlower;
+
 #[derive(AssetCollection, Resource)]
 pub struct EnemyAssets {
-    #[asset(path = "sprites/spike_flower.png")]
-    spike_flower: Handle<Image>,
+    #[asset(path = "sprites/spike_flower.aseprite")]
+    spike_flower: Handle<SpriteSheet>,
 }
 
-fn init_flowers(mut commands: Commands, config: GameConfig, assets: Res<EnemyAssets>) {
+pub fn init_flowers(
+    mut commands: Commands,
+    config: GameConfig,
+    mut rng: ResMut<GameRng>,
+    // Sprites aren't loaded when running headless
+    assets: Option<Res<EnemyAssets>>,
+) {
     let Some(config) = config.get() else {
         error!("unable to get config file; not spawning flowers");
         return;
     };
 
-    let mut rng = thread_rng();
+    let rng = rng.rng();
 
     let world_size = config.world_size;
 
@@ -67,28 +95,48 @@ fn init_flowers(mut commands: Commands, config: GameConfig, assets: Res<EnemyAss
         let angle = rng.gen_range(0.0f32..TAU);
         let rot = Quat::from_axis_angle(Vec3::Z, angle);
 
-        commands.spawn((
+        let mut flower = commands.spawn((
             Enemy,
+            FlowerKind::Spike,
             GameObject,
-            SpriteBundle {
+            SpriteSheetBundle {
                 transform: Transform::from_translation(Vec3::new(x, y, 0.)).with_rotation(rot),
-                texture: assets.spike_flower.clone(),
                 ..default()
             },
         ));
+
+        if let Some(assets) = &assets {
+            // Cosmetic only, so it doesn't draw from the game's seeded RNG
+            let offset = Duration::from_millis(rand::thread_rng().gen_range(0..1000));
+            flower.insert((
+                SpriteAnimation::repeating(assets.spike_flower.clone(), "Sway")
+                    .with_elapsed(offset),
+                // Pop up out of the ground
+                Animator::new(Tween::new(
+                    Ease::BackOut,
+                    Duration::from_millis(300) + offset / 2,
+                    ScaleLens {
+                        start: Vec3::ZERO,
+                        end: Vec3::ONE,
+                    },
+                )),
+            ));
+        }
     }
 }
 
 #[derive(Debug, Event)]
 pub struct PickFlower(pub Entity);
 
-fn detect_proximity(
+// Checks the whole path the player moved along this tick, so a fast goose can't skip over flowers
+// between one position and the next. Flowers are picked in the order the goose reached them.
+pub fn detect_proximity(
     config: GameConfig,
-    player: Query<&Transform, With<Player>>,
+    player: Query<(&Transform, &PreviousPosition), With<Player>>,
     enemies: Query<(Entity, &Transform), With<Enemy>>,
     mut events: EventWriter<PickFlower>,
 ) {
-    let Ok(player) = player.get_single() else {
+    let Ok((player, previous)) = player.get_single() else {
         return;
     };
 
@@ -96,18 +144,73 @@ fn detect_proximity(
         return;
     };
 
-    for (enemy, transform) in &enemies {
-        let dist_squared = player.translation.distance_squared(transform.translation);
-        let min_dist = config.flower_pickup_range * config.flower_pickup_range;
+    let start = **previous;
+    let end = player.translation.truncate();
 
-        if dist_squared <= min_dist {
-            events.send(PickFlower(enemy));
-        }
+    let mut picked = enemies
+        .iter()
+        .filter_map(|(enemy, transform)| {
+            let center = transform.translation.truncate();
+            sweep_circle(start, end, center, config.flower_pickup_range).map(|t| (t, enemy))
+        })
+        .collect::<Vec<_>>();
+
+    picked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    for (_, enemy) in picked {
+        events.send(PickFlower(enemy));
     }
 }
 
-fn despawn_flower_when_picked(mut commands: Commands, mut events: EventReader<PickFlower>) {
+// Returns how far along the segment from `start` to `end` (0 to 1) it first comes within `radius`
+// of `center`, if it does at all.
+fn sweep_circle(start: Vec2, end: Vec2, center: Vec2, radius: f32) -> Option<f32> {
+    let offset = start - center;
+    let c = offset.length_squared() - radius * radius;
+    if c <= 0. {
+        // Already in range at the start
+        return Some(0.);
+    }
+
+    let path = end - start;
+    let a = path.length_squared();
+    if a == 0. {
+        return None;
+    }
+
+    // Solve |offset + path * t| = radius for the first t
+    let b = 2. * offset.dot(path);
+    let discriminant = b * b - 4. * a * c;
+    if discriminant < 0. {
+        return None;
+    }
+
+    let t = (-b - discriminant.sqrt()) / (2. * a);
+    (0. ..=1.).contains(&t).then_some(t)
+}
+
+// The flower itself goes straight away so nothing else can pick it, but when there are sprites a
+// stand-in is left behind to play the pickup animation.
+fn despawn_flower_when_picked(
+    mut commands: Commands,
+    mut events: EventReader<PickFlower>,
+    flowers: Query<&Transform, With<Enemy>>,
+    assets: Option<Res<EnemyAssets>>,
+) {
     for event in events.read() {
+        if let (Some(assets), Ok(transform)) = (&assets, flowers.get(event.0)) {
+            commands.spawn((
+                GameObject,
+                PickedFlower,
+                SpriteSheetBundle {
+                    transform: *transform,
+                    ..default()
+                },
+                SpriteAnimation::once(assets.spike_flower.clone(), "Pickup"),
+                DespawnWhenFinished,
+            ));
+        }
+
         commands.entity(event.0).despawn_recursive();
     }
 }
@@ -0,0 +1,270 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    animation::{DespawnWhenFinished, SpriteAnimation, SpriteSheet},
+    camera::MainCamera,
+    enemy::PickedFlower,
+    game::GameObject,
+    tween::{Animator, Ease, TextColorLens, TranslationLens, Tween, TweenCompleted},
+    ui::{FlowerCountText, UiAssets},
+};
+
+// Makes picking a flower feel like something happened: the flower flies off to the counter in the
+// HUD, a "+1" floats up from where it was, and a few particles burst out.
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PickupFeedback>().add_systems(
+            Update,
+            (
+                start_pickup_feedback,
+                fly_to_counter,
+                remove_faded_popups,
+                move_particles,
+            )
+                .chain(),
+        );
+    }
+}
+
+// How long each part of the pickup feedback lasts, and what it looks like
+#[derive(Debug, Clone, Resource)]
+pub struct PickupFeedback {
+    // Seconds for a picked flower to reach the counter. Flowers with a pickup animation take as
+    // long as it does instead, so it finishes as they arrive.
+    pub fly_time: f32,
+    // Seconds the "+1" stays on screen, and how far it rises in that time
+    pub text_time: f32,
+    pub text_rise: f32,
+    pub text_color: Color,
+    pub particle_count: usize,
+    pub particle_lifetime: f32,
+    // Starting speed in pixels per second, which slows down over the particle's lifetime
+    pub particle_speed: f32,
+    pub particle_size: f32,
+    // Each particle gets one of these at random
+    pub particle_colors: Vec<Color>,
+}
+
+impl Default for PickupFeedback {
+    fn default() -> Self {
+        Self {
+            fly_time: 0.3,
+            text_time: 0.6,
+            text_rise: 40.,
+            text_color: Color::rgb(1., 0.9, 0.3),
+            particle_count: 12,
+            particle_lifetime: 0.4,
+            particle_speed: 180.,
+            particle_size: 4.,
+            particle_colors: vec![
+                Color::rgb(1., 0.55, 0.1),
+                Color::rgb(1., 0.85, 0.2),
+                Color::rgb(0.2, 0.6, 0.15),
+            ],
+        }
+    }
+}
+
+// Drawn above the flowers and the goose
+const FEEDBACK_Z: f32 = 10.;
+
+#[derive(Component)]
+struct FlyToCounter {
+    start: Vec3,
+    // Seconds the whole flight takes
+    time: f32,
+    elapsed: f32,
+}
+
+// The "+1" text
+#[derive(Component)]
+struct Popup;
+
+// Sent by a popup's fade tween when it's done
+const POPUP_FADED: u64 = 1;
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    elapsed: f32,
+}
+
+fn start_pickup_feedback(
+    mut commands: Commands,
+    settings: Res<PickupFeedback>,
+    assets: Option<Res<UiAssets>>,
+    sheets: Res<Assets<SpriteSheet>>,
+    picked: Query<(Entity, &Transform, Option<&SpriteAnimation>), Added<PickedFlower>>,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (entity, transform, animation) in &picked {
+        let start = transform.translation.truncate().extend(FEEDBACK_Z);
+
+        // The flight decides when the flower goes, rather than its pickup animation
+        let fly_time = animation
+            .and_then(|animation| {
+                let sheet = sheets.get(animation.sheet())?;
+                sheet.tag_duration(animation.tag())
+            })
+            .map_or(settings.fly_time, |duration| duration.as_secs_f32());
+        commands
+            .entity(entity)
+            .remove::<DespawnWhenFinished>()
+            .insert(FlyToCounter {
+                start,
+                time: fly_time,
+                elapsed: 0.,
+            });
+
+        if let Some(assets) = &assets {
+            // Rises quickly then settles, fading out as it goes. A negative time shows it for no
+            // time at all, and an infinite one forever.
+            let time =
+                Duration::try_from_secs_f32(settings.text_time.max(0.)).unwrap_or(Duration::MAX);
+            let transparent = *settings.text_color.clone().set_a(0.);
+
+            commands.spawn((
+                GameObject,
+                Popup,
+                Text2dBundle {
+                    text: Text::from_section(
+                        "+1",
+                        TextStyle {
+                            font: assets.font.clone(),
+                            font_size: 24.,
+                            color: settings.text_color,
+                        },
+                    ),
+                    transform: Transform::from_translation(start),
+                    ..default()
+                },
+                Animator::new(Tween::new(
+                    Ease::QuadOut,
+                    time,
+                    TranslationLens {
+                        start,
+                        end: start + Vec3::Y * settings.text_rise,
+                    },
+                )),
+                Animator::new(
+                    Tween::new(
+                        Ease::Linear,
+                        time,
+                        TextColorLens {
+                            start: settings.text_color,
+                            end: transparent,
+                        },
+                    )
+                    .with_completed_event(POPUP_FADED),
+                ),
+            ));
+        }
+
+        for _ in 0..settings.particle_count {
+            let angle = rng.gen_range(0.0..TAU);
+            let speed = settings.particle_speed * rng.gen_range(0.5..1.);
+            let color = settings
+                .particle_colors
+                .get(rng.gen_range(0..settings.particle_colors.len().max(1)))
+                .copied()
+                .unwrap_or(Color::WHITE);
+
+            commands.spawn((
+                GameObject,
+                Particle {
+                    velocity: Vec2::from_angle(angle) * speed,
+                    elapsed: 0.,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(Vec2::splat(settings.particle_size)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(start),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn fly_to_counter(
+    mut commands: Commands,
+    time: Res<Time>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    counter: Query<&GlobalTransform, With<FlowerCountText>>,
+    mut flowers: Query<(Entity, &mut FlyToCounter, &mut Transform)>,
+) {
+    // UI nodes are positioned in the window, so find where the counter is in the world. It's
+    // looked up every frame in case the camera moves. The window can be bigger than the camera's
+    // viewport when it's letterboxed, so the position is made relative to the viewport first.
+    let target = camera
+        .get_single()
+        .ok()
+        .zip(counter.get_single().ok())
+        .and_then(|((camera, camera_transform), counter)| {
+            let origin = camera
+                .logical_viewport_rect()
+                .map(|viewport| viewport.min)
+                .unwrap_or_default();
+            let position = counter.translation().truncate() - origin;
+            camera.viewport_to_world_2d(camera_transform, position)
+        });
+
+    for (entity, mut flight, mut transform) in &mut flowers {
+        flight.elapsed += time.delta_seconds();
+        let t = flight.elapsed / flight.time.max(f32::EPSILON);
+
+        let Some(target) = target.filter(|_| t < 1.) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        // Starts slowly and speeds up, as if it's being pulled in
+        let eased = t * t;
+        transform.translation = flight.start.lerp(target.extend(FEEDBACK_Z), eased);
+    }
+}
+
+fn remove_faded_popups(
+    mut commands: Commands,
+    mut completed: EventReader<TweenCompleted>,
+    popups: Query<(), With<Popup>>,
+) {
+    for event in completed.read() {
+        if event.id == POPUP_FADED && popups.contains(event.entity) {
+            commands.entity(event.entity).despawn_recursive();
+        }
+    }
+}
+
+fn move_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<PickupFeedback>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_seconds();
+
+    for (entity, mut particle, mut transform, mut sprite) in &mut particles {
+        particle.elapsed += delta;
+        let t = particle.elapsed / settings.particle_lifetime.max(f32::EPSILON);
+        if t >= 1. {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Slow down, shrink and fade over the particle's lifetime
+        let velocity = particle.velocity * (1. - t);
+        transform.translation += (velocity * delta).extend(0.);
+        transform.scale = Vec3::splat(1. - t);
+        sprite.color.set_a(1. - t);
+    }
+}
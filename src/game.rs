@@ -1,15 +1,108 @@
 use bevy::prelude::*;
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 
-use crate::{assets::GameConfig, GameState};
+use crate::{assets::GameConfig, settings::SettingsState, GameState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
+pub enum GameSet {
+    SeedRng,
+}
 
 pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Gaming), init_game_timer)
+        app.init_resource::<SeedOverride>()
+            .init_resource::<GameFrame>()
+            .add_systems(
+                OnEnter(GameState::Gaming),
+                (
+                    init_game_rng.in_set(GameSet::SeedRng),
+                    init_game_timer,
+                    reset_game_frame,
+                    configure_fixed_timestep,
+                ),
+            )
+            // The timer stays until the game is over, so systems still running this frame can
+            // read it after it's finished.
+            .add_systems(OnExit(GameState::Gaming), remove_game_timer)
             .add_systems(OnExit(GameState::GameOver), cleanup_game_objects)
-            .add_systems(Update, tick_game_timer.run_if(in_state(GameState::Gaming)));
+            .add_systems(
+                FixedUpdate,
+                tick_game_timer
+                    .run_if(in_state(GameState::Gaming))
+                    // The clock stops while the settings screen is open
+                    .run_if(in_state(SettingsState::Closed)),
+            )
+            .add_systems(
+                FixedLast,
+                advance_game_frame
+                    .run_if(in_state(GameState::Gaming))
+                    .run_if(in_state(SettingsState::Closed)),
+            );
+    }
+}
+
+// Forces the seed used for the next run instead of picking a random one, e.g. when replaying.
+#[derive(Default, Debug, Resource)]
+pub struct SeedOverride(pub Option<u64>);
+
+// All randomness in a run comes from here, so a run can be reproduced from its seed.
+#[derive(Debug, Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+}
+
+fn init_game_rng(mut commands: Commands, seed: Res<SeedOverride>) {
+    let seed = seed.0.unwrap_or_else(|| thread_rng().gen());
+    info!("Starting run with seed {}", seed);
+    commands.insert_resource(GameRng::new(seed));
+}
+
+fn configure_fixed_timestep(config: GameConfig, mut time: ResMut<Time<Fixed>>) {
+    let Some(config) = config.get() else {
+        return;
+    };
+
+    if config.simulation_hz <= 0. {
+        error!(
+            "simulation_hz must be positive, got {}; keeping the default tick rate",
+            config.simulation_hz
+        );
+        return;
     }
+
+    time.set_timestep_hz(config.simulation_hz);
+}
+
+// Number of simulation ticks since the current run started
+#[derive(Default, Debug, Clone, Copy, Deref, Resource)]
+pub struct GameFrame(u64);
+
+fn reset_game_frame(mut frame: ResMut<GameFrame>) {
+    frame.0 = 0;
+}
+
+fn advance_game_frame(mut frame: ResMut<GameFrame>) {
+    frame.0 += 1;
 }
 
 #[derive(Debug, Deref, DerefMut, Resource)]
@@ -29,17 +122,19 @@ fn init_game_timer(mut commands: Commands, config: GameConfig) {
 }
 
 fn tick_game_timer(
-    mut commands: Commands,
     time: Res<Time>,
     mut timer: ResMut<GameTimer>,
     mut next: ResMut<NextState<GameState>>,
 ) {
     if timer.tick(time.delta()).just_finished() {
-        commands.remove_resource::<GameTimer>();
         next.set(GameState::GameOver);
     }
 }
 
+fn remove_game_timer(mut commands: Commands) {
+    commands.remove_resource::<GameTimer>();
+}
+
 #[derive(Default, Debug, Component)]
 pub struct GameObject;
 
@@ -0,0 +1,283 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+use bevy_asset_loader::{
+    asset_collection::AssetCollection,
+    loading_state::{
+        config::{ConfigureLoadingState, LoadingStateConfig},
+        LoadingStateAppExt,
+    },
+};
+use rand::Rng;
+
+use crate::{assets::GameConfig, game::GameObject, GameState};
+
+// The garden under everything: grass with patches of dirt, laid out from noise so it's different
+// every run, and a scattering of flowers that are just for show.
+pub struct GroundPlugin;
+
+impl Plugin for GroundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ground>()
+            .configure_loading_state(
+                LoadingStateConfig::new(GameState::Loading).load_collection::<GroundAssets>(),
+            )
+            .add_systems(Startup, init_tileset)
+            .add_systems(OnEnter(GameState::Gaming), spawn_ground);
+    }
+}
+
+// How the ground is laid out. The tileset is drawn at `tile_size` once at startup, and the rest is
+// read each time a game starts.
+#[derive(Debug, Clone, Resource)]
+pub struct Ground {
+    // Width and height of a tile, in pixels
+    pub tile_size: u32,
+    // Roughly how many tiles across a patch of dirt is
+    pub patch_size: f32,
+    // Tiles where the noise, which goes from 0 to 1 but mostly lands near the middle, is below this
+    // are dirt. It isn't the fraction of dirt: 0.3 gives small scattered patches, 0.5 about half.
+    pub dirt: f32,
+    pub props: usize,
+}
+
+impl Default for Ground {
+    fn default() -> Self {
+        Self {
+            tile_size: 32,
+            patch_size: 6.,
+            dirt: 0.3,
+            props: 150,
+        }
+    }
+}
+
+// Beneath the flowers and the goose, with the props between them and the tiles
+const GROUND_Z: f32 = -20.;
+const PROP_Z: f32 = -10.;
+
+// Each kind of tile comes in a few variants so the ground doesn't look like a grid
+const TILE_VARIANTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tile {
+    Grass,
+    Dirt,
+}
+
+impl Tile {
+    // Row of the tileset the tile's variants are in
+    fn row(self) -> usize {
+        match self {
+            Tile::Grass => 0,
+            Tile::Dirt => 1,
+        }
+    }
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct GroundAssets {
+    #[asset(
+        paths(
+            "sprites/flower_blue.png",
+            "sprites/flower_purple.png",
+            "sprites/flower_white.png"
+        ),
+        collection(typed)
+    )]
+    props: Vec<Handle<Image>>,
+}
+
+#[derive(Resource)]
+struct Tileset {
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+// Draws every variant of every tile into one texture, a row per kind of tile.
+fn init_tileset(
+    mut commands: Commands,
+    settings: Res<Ground>,
+    mut images: ResMut<Assets<Image>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+) {
+    let size = settings.tile_size.max(1);
+    let width = size * TILE_VARIANTS as u32;
+    let height = size * 2;
+    let mut data = vec![0; (width * height * 4) as usize];
+
+    for tile in [Tile::Grass, Tile::Dirt] {
+        for variant in 0..TILE_VARIANTS {
+            for y in 0..size {
+                for x in 0..size {
+                    let color = tile_pixel(tile, variant as u32, x, y);
+                    let px = variant as u32 * size + x;
+                    let py = tile.row() as u32 * size + y;
+                    let start = ((py * width + px) * 4) as usize;
+                    data[start..start + 4].copy_from_slice(&color.as_rgba_u8());
+                }
+            }
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+
+    let layout =
+        TextureAtlasLayout::from_grid(Vec2::splat(size as f32), TILE_VARIANTS, 2, None, None);
+
+    commands.insert_resource(Tileset {
+        texture: images.add(image),
+        layout: layouts.add(layout),
+    });
+}
+
+// Grass is speckled with darker blades, dirt with the odd pebble.
+fn tile_pixel(tile: Tile, variant: u32, x: u32, y: u32) -> Color {
+    let speckle = hash(x, y, variant * 2 + tile.row() as u32);
+    let shade = 0.95 + 0.1 * hash(x, y, variant + 100);
+    let [r, g, b] = match tile {
+        Tile::Grass if speckle > 0.9 => [0.24, 0.45, 0.17],
+        Tile::Grass => [0.36, 0.6, 0.25],
+        Tile::Dirt if speckle > 0.95 => [0.68, 0.6, 0.5],
+        Tile::Dirt => [0.55, 0.4, 0.26],
+    };
+    Color::rgb(r * shade, g * shade, b * shade)
+}
+
+fn spawn_ground(
+    mut commands: Commands,
+    config: GameConfig,
+    settings: Res<Ground>,
+    tileset: Res<Tileset>,
+    // Sprites aren't loaded when running headless
+    assets: Option<Res<GroundAssets>>,
+) {
+    let Some(config) = config.get() else {
+        return;
+    };
+
+    // Cosmetic only, so it doesn't draw from the game's seeded RNG
+    let mut rng = rand::thread_rng();
+    let seed = rng.gen();
+
+    // Flowers are spawned from -world_size to world_size, so cover all of that
+    let tile_size = settings.tile_size.max(1) as f32;
+    let tiles = (config.world_size * 2. / tile_size).ceil() as i32;
+    if tiles <= 0 {
+        return;
+    }
+    let origin = -(tiles as f32) * tile_size / 2. + tile_size / 2.;
+    let tile_at = |x: i32, y: i32| {
+        let patches = settings.patch_size.max(f32::EPSILON);
+        let noise = fractal_noise(Vec2::new(x as f32, y as f32) / patches, seed);
+        if noise < settings.dirt {
+            Tile::Dirt
+        } else {
+            Tile::Grass
+        }
+    };
+
+    for y in 0..tiles {
+        for x in 0..tiles {
+            let tile = tile_at(x, y);
+            let variant = rng.gen_range(0..TILE_VARIANTS);
+            let position = Vec2::new(x as f32, y as f32) * tile_size + origin;
+
+            commands.spawn((
+                GameObject,
+                SpriteSheetBundle {
+                    texture: tileset.texture.clone(),
+                    atlas: TextureAtlas {
+                        layout: tileset.layout.clone(),
+                        index: tile.row() * TILE_VARIANTS + variant,
+                    },
+                    transform: Transform::from_translation(position.extend(GROUND_Z)),
+                    ..default()
+                },
+            ));
+        }
+    }
+
+    let Some(assets) = assets else {
+        return;
+    };
+    if assets.props.is_empty() {
+        return;
+    }
+
+    // Props only grow on grass
+    let mut placed = 0;
+    for _ in 0..settings.props * 10 {
+        if placed == settings.props {
+            break;
+        }
+
+        let x = rng.gen_range(0..tiles);
+        let y = rng.gen_range(0..tiles);
+        if tile_at(x, y) != Tile::Grass {
+            continue;
+        }
+
+        let jitter = Vec2::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5));
+        let position = (Vec2::new(x as f32, y as f32) + jitter) * tile_size + origin;
+        let prop = &assets.props[rng.gen_range(0..assets.props.len())];
+
+        commands.spawn((
+            GameObject,
+            SpriteBundle {
+                texture: prop.clone(),
+                transform: Transform::from_translation(position.extend(PROP_Z))
+                    .with_scale(Vec3::splat(0.5)),
+                ..default()
+            },
+        ));
+        placed += 1;
+    }
+}
+
+// Smooth noise from 0 to 1, made of two layers of value noise so patches have ragged edges.
+fn fractal_noise(position: Vec2, seed: u32) -> f32 {
+    let coarse = value_noise(position, seed);
+    let fine = value_noise(position * 2., seed.wrapping_add(1));
+    (coarse * 2. + fine) / 3.
+}
+
+// Random values at whole coordinates, blended smoothly in between.
+fn value_noise(position: Vec2, seed: u32) -> f32 {
+    let cell = position.floor();
+    let t = position - cell;
+    // Smoothstep, so the blend doesn't show creases at cell edges
+    let t = t * t * (3. - 2. * t);
+
+    let corner = |dx: i32, dy: i32| hash(cell.x as i32 + dx, cell.y as i32 + dy, seed);
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * t.x;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * t.x;
+    bottom + (top - bottom) * t.y
+}
+
+// A repeatable random number from 0 to 1 for a point
+fn hash(x: impl Into<i64>, y: impl Into<i64>, seed: u32) -> f32 {
+    let mut h = (x.into() as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y.into() as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ (seed as u64).wrapping_mul(0x1656_67B1_9E37_79F9);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    h ^= h >> 33;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
@@ -0,0 +1,188 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    app::Plugins, input::InputPlugin as DeviceInputPlugin, prelude::*, time::TimeUpdateStrategy,
+    window::ExitCondition,
+};
+use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
+
+use crate::{
+    assets::{AssetsPlugin, GameAssetCollection, GameConfigFile, GameConfigLoadError},
+    autopilot::{Autopilot, AutopilotPlugin, Strategy},
+    enemy::EnemyPlugin,
+    game::GamePlugin,
+    input::InputPlugin,
+    interpolation::InterpolationPlugin,
+    player::{FlowerCount, Player, PlayerPlugin},
+    settings::SettingsState,
+    tween::TweenPlugin,
+    GameState,
+};
+
+// Runs the gameplay without a window, renderer or audio, stepping time by a fixed amount each
+// frame, so that a run can be simulated and inspected on a machine without a GPU.
+pub struct HeadlessApp {
+    app: App,
+}
+
+impl HeadlessApp {
+    pub fn new(config: GameConfigFile) -> Self {
+        // One simulation tick per frame
+        let frame_time = if config.simulation_hz > 0. {
+            Duration::from_secs_f64(1. / config.simulation_hz)
+        } else {
+            Time::<Fixed>::default().timestep()
+        };
+
+        let mut app = App::new();
+
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            DeviceInputPlugin,
+            // Only for the window events that input listens to; no window is opened
+            WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            },
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(frame_time));
+
+        // The config is handed over up front and nothing else is loaded, so the game starts
+        // straight away. The loading state is still added, since plugins add their assets to it,
+        // but it's never entered.
+        app.insert_state(GameState::Gaming)
+            .init_state::<SettingsState>()
+            .add_loading_state(LoadingState::new(GameState::Loading));
+
+        app.add_plugins((
+            AssetsPlugin,
+            AutopilotPlugin,
+            EnemyPlugin,
+            GamePlugin,
+            InputPlugin,
+            InterpolationPlugin,
+            PlayerPlugin,
+            TweenPlugin,
+        ));
+
+        let game_config = app
+            .world
+            .resource_mut::<Assets<GameConfigFile>>()
+            .add(config);
+        app.insert_resource(GameAssetCollection { game_config });
+
+        Self { app }
+    }
+
+    // Adds more of the game, e.g. the UI, to check it works with the gameplay. Anything that needs
+    // loaded assets has to cope without them.
+    pub fn add_plugins<M>(&mut self, plugins: impl Plugins<M>) -> &mut Self {
+        self.app.add_plugins(plugins);
+        self
+    }
+
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, GameConfigLoadError> {
+        Ok(Self::new(GameConfigFile::from_file(path)?))
+    }
+
+    // Runs the given number of frames
+    pub fn step(&mut self, frames: u32) {
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    // Lets the autopilot play from now on, instead of leaving the player standing still.
+    pub fn enable_autopilot(&mut self, strategy: Strategy) {
+        Autopilot::enable(self.world_mut(), strategy);
+    }
+
+    pub fn state(&self) -> GameState {
+        *self.world().resource::<State<GameState>>().get()
+    }
+
+    // The player's score, if there is a player
+    pub fn flower_count(&mut self) -> Option<i32> {
+        let world = self.world_mut();
+        world
+            .query_filtered::<&FlowerCount, With<Player>>()
+            .get_single(world)
+            .ok()
+            .map(|count| count.0)
+    }
+
+    pub fn world(&self) -> &World {
+        &self.app.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+}
+
+// Options for simulating a run from the command line, e.g.
+// `--headless 640 --config my.json --autopilot route`
+pub struct HeadlessArgs {
+    pub frames: u32,
+    pub config: PathBuf,
+    pub autopilot: Option<Strategy>,
+}
+
+impl HeadlessArgs {
+    // Nothing unless `--headless` was given. The autopilot comes from `Strategy::from_args`, which
+    // the windowed game needs too.
+    pub fn from_args(autopilot: Option<Strategy>) -> Option<Self> {
+        let mut frames = None;
+        let mut config = PathBuf::from("assets/game_config.json");
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => frames = args.next().and_then(|n| n.parse().ok()),
+                "--config" => config = args.next().map(PathBuf::from).unwrap_or(config),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            frames: frames?,
+            config,
+            autopilot,
+        })
+    }
+
+    // There's no logging without the default plugins, so results go to stdout.
+    pub fn run(&self) {
+        let mut app = match HeadlessApp::from_config_file(&self.config) {
+            Ok(app) => app,
+            Err(err) => {
+                eprintln!("Unable to load {}: {:?}", self.config.display(), err);
+                std::process::exit(1);
+            }
+        };
+
+        if let Some(strategy) = self.autopilot {
+            app.enable_autopilot(strategy);
+        }
+
+        app.step(self.frames);
+
+        let player = match self.autopilot {
+            Some(strategy) => format!("Autopilot ({:?})", strategy),
+            None => "Player".to_string(),
+        };
+
+        println!(
+            "{} collected {} flowers after {} frames ({:?})",
+            player,
+            app.flower_count().unwrap_or_default(),
+            self.frames,
+            app.state()
+        );
+    }
+}
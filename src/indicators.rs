@@ -0,0 +1,179 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use crate::{camera::MainCamera, enemy::Enemy, game::GameObject, player::Player, GameState};
+
+// Arrows around the edge of the screen pointing at the flowers closest to the goose that can't be
+// seen, so there's always somewhere to head for.
+pub struct IndicatorsPlugin;
+
+impl Plugin for IndicatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OffscreenIndicators>()
+            .add_systems(Startup, init_arrow_image)
+            .add_systems(OnEnter(GameState::Gaming), spawn_indicators)
+            .add_systems(Update, point_indicators.run_if(in_state(GameState::Gaming)));
+    }
+}
+
+// How many flowers get an arrow, and how the arrows are sized and colored
+#[derive(Debug, Clone, Resource)]
+pub struct OffscreenIndicators {
+    // How many of the nearest off screen flowers get an arrow
+    pub count: usize,
+    // Gap between the arrows and the edge of the screen, in pixels
+    pub margin: f32,
+    // Arrows shrink from `near_scale` to `far_scale` as the flower gets up to `far_distance` away
+    // from the goose
+    pub near_scale: f32,
+    pub far_scale: f32,
+    pub far_distance: f32,
+    pub color: Color,
+}
+
+impl Default for OffscreenIndicators {
+    fn default() -> Self {
+        Self {
+            count: 3,
+            margin: 24.,
+            near_scale: 1.5,
+            far_scale: 0.5,
+            far_distance: 1000.,
+            color: Color::rgb(1., 0.55, 0.1),
+        }
+    }
+}
+
+// Drawn above everything else in the world
+const INDICATOR_Z: f32 = 20.;
+
+// Size of the arrow image, which points to the right
+const ARROW_SIZE: u32 = 16;
+
+#[derive(Resource)]
+struct ArrowImage(Handle<Image>);
+
+#[derive(Component)]
+struct Indicator;
+
+// A white triangle, so the sprite's color decides what color it ends up
+fn init_arrow_image(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let half = ARROW_SIZE as f32 / 2.;
+    let mut data = Vec::with_capacity((ARROW_SIZE * ARROW_SIZE * 4) as usize);
+    for y in 0..ARROW_SIZE {
+        for x in 0..ARROW_SIZE {
+            // Narrows from the full height at the left edge to a point at the right
+            let from_middle = (y as f32 + 0.5 - half).abs();
+            let inside = from_middle <= half * (1. - (x as f32 + 0.5) / ARROW_SIZE as f32);
+            let alpha = if inside { 255 } else { 0 };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+
+    let mut image = Image::new(
+        Extent3d {
+            width: ARROW_SIZE,
+            height: ARROW_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = ImageSampler::nearest();
+    commands.insert_resource(ArrowImage(images.add(image)));
+}
+
+fn spawn_indicators(
+    mut commands: Commands,
+    settings: Res<OffscreenIndicators>,
+    arrow: Res<ArrowImage>,
+) {
+    for _ in 0..settings.count {
+        commands.spawn((
+            Indicator,
+            GameObject,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: settings.color,
+                    ..default()
+                },
+                texture: arrow.0.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+}
+
+fn point_indicators(
+    settings: Res<OffscreenIndicators>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    player: Query<&Transform, (With<Player>, Without<Indicator>)>,
+    flowers: Query<&Transform, (With<Enemy>, Without<Indicator>)>,
+    mut indicators: Query<(&mut Transform, &mut Visibility), With<Indicator>>,
+) {
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+    let Some(size) = camera.logical_viewport_size() else {
+        return;
+    };
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    let goose = player.translation.truncate();
+    let mut nearest = flowers
+        .iter()
+        .map(|flower| flower.translation.truncate())
+        .map(|flower| (flower.distance_squared(goose), flower))
+        .collect::<Vec<_>>();
+    nearest.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    let center = size / 2.;
+    let inner = (center - settings.margin).max(Vec2::ONE);
+    // Flowers already on screen don't need an arrow, so they don't use one up
+    let mut targets = nearest
+        .into_iter()
+        .filter_map(|(_, flower)| {
+            let on_screen = camera.world_to_viewport(camera_transform, flower.extend(0.))?;
+            let offset = on_screen - center;
+            if offset.x.abs() <= center.x && offset.y.abs() <= center.y {
+                return None;
+            }
+
+            // Slide in from the flower along the line to the middle of the screen until it's
+            // within the margin
+            let fit = (inner / offset.abs()).min_element();
+            let edge = camera.viewport_to_world_2d(camera_transform, center + offset * fit)?;
+            Some((edge, flower))
+        })
+        .take(settings.count);
+
+    for (mut transform, mut visibility) in &mut indicators {
+        let Some((edge, flower)) = targets.next() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+
+        // Measured from the goose like the sorting, since the camera doesn't follow it and the
+        // edge of the screen says nothing about how far it has to walk
+        let distance = goose.distance(flower);
+        let t = (distance / settings.far_distance.max(f32::EPSILON)).min(1.);
+        let scale = settings.near_scale + (settings.far_scale - settings.near_scale) * t;
+        let direction = flower - edge;
+
+        *transform = Transform::from_translation(edge.extend(INDICATOR_Z))
+            .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)))
+            .with_scale(Vec3::splat(scale));
+        *visibility = Visibility::Inherited;
+    }
+}
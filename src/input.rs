@@ -1,7 +1,20 @@
+use std::{fs, path::PathBuf};
+
 use bevy::{
-    input::{keyboard::KeyboardInput, ButtonState},
+    input::{
+        gamepad::{GamepadConnection, GamepadConnectionEvent},
+        keyboard::KeyboardInput,
+        ButtonState, InputSystem,
+    },
     prelude::*,
-    utils::HashMap,
+    utils::{thiserror::Error, HashMap},
+    window::{PrimaryWindow, WindowFocused, WindowOccluded},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    camera::{MainCamera, RESET_ZOOM_KEYS, ZOOM_IN_KEYS, ZOOM_OUT_KEYS},
+    minimap::MINIMAP_KEY,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemSet)]
@@ -14,11 +27,33 @@ pub struct InputPlugin;
 impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<InputState>()
-            .add_systems(Update, process_keyboard_input.in_set(InputSet::ReadInput));
+            .init_resource::<InputSource>()
+            .init_resource::<BindingsFile>()
+            .init_resource::<InputBindings>()
+            .add_systems(Startup, load_bindings)
+            // Device events only last a couple of frames, and there can be more frames than that
+            // between ticks, so they're read every frame. What they press and release is kept in
+            // `InputState` for the next tick to see.
+            .add_systems(
+                PreUpdate,
+                (
+                    release_inputs_on_rebind,
+                    release_inputs_on_focus_lost,
+                    process_keyboard_input,
+                    process_gamepad_input,
+                    process_pointer_input,
+                )
+                    .chain()
+                    .after(InputSystem)
+                    .in_set(InputSet::ReadInput)
+                    .run_if(resource_equals(InputSource::Devices)),
+            )
+            // Edges only last for the tick they're seen in
+            .add_systems(FixedLast, clear_input_edges);
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Input {
     MoveLeft,
     MoveRight,
@@ -27,6 +62,10 @@ pub enum Input {
 }
 
 impl Input {
+    pub fn all() -> &'static [Input] {
+        Self::movement()
+    }
+
     fn movement() -> &'static [Input] {
         &[
             Input::MoveLeft,
@@ -44,43 +83,447 @@ impl Input {
             Input::MoveDown => Some(Vec2::NEG_Y),
         }
     }
+
+    // The D-pad mirrors the movement keys; it isn't rebindable.
+    fn dpad_button(&self) -> GamepadButtonType {
+        match self {
+            Input::MoveLeft => GamepadButtonType::DPadLeft,
+            Input::MoveRight => GamepadButtonType::DPadRight,
+            Input::MoveUp => GamepadButtonType::DPadUp,
+            Input::MoveDown => GamepadButtonType::DPadDown,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Input::MoveLeft => "Move Left",
+            Input::MoveRight => "Move Right",
+            Input::MoveUp => "Move Up",
+            Input::MoveDown => "Move Down",
+        }
+    }
+}
+
+// Where `InputState` gets filled in from. Anything other than `Devices` turns off the keyboard,
+// gamepad and pointer systems, and is expected to write `InputState` itself during
+// `InputSet::ReadInput`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum InputSource {
+    #[default]
+    Devices,
+    Playback,
+    Autopilot,
 }
 
 #[derive(Default, Debug, Resource)]
 pub struct InputState {
-    buttons: HashMap<Input, bool>,
+    // Tracks held actions along with the frames they were pressed or released on, so systems can
+    // react to taps as well as holds.
+    buttons: ButtonInput<Input>,
+    // Analog movement from a gamepad stick, already passed through the dead-zone and response
+    // curve. Its length is in 0..=1.
+    stick: Vec2,
+    // A world-space point picked with the mouse or a touch, for the player to walk toward.
+    target: Option<Vec2>,
 }
 
 impl InputState {
-    pub fn normalized_direction(&self) -> Vec2 {
-        Input::movement()
+    // The direction to move in, scaled by how far the stick is tilted. Digital input (keys or the
+    // D-pad) always has full magnitude and takes priority over the stick.
+    pub fn direction(&self) -> Vec2 {
+        let digital = Input::movement()
             .iter()
-            .filter(|input| self.buttons.get(*input).copied().unwrap_or(false))
+            .filter(|input| self.buttons.pressed(**input))
             .filter_map(|input| input.direction())
             .sum::<Vec2>()
-            .normalize_or_zero()
+            .normalize_or_zero();
+
+        if digital != Vec2::ZERO {
+            digital
+        } else {
+            self.stick
+        }
+    }
+
+    pub fn normalized_direction(&self) -> Vec2 {
+        self.direction().normalize_or_zero()
     }
 
     pub fn get(&self, input: Input) -> bool {
-        self.buttons.get(&input).copied().unwrap_or(false)
+        self.buttons.pressed(input)
+    }
+
+    pub fn just_pressed(&self, input: Input) -> bool {
+        self.buttons.just_pressed(input)
+    }
+
+    pub fn just_released(&self, input: Input) -> bool {
+        self.buttons.just_released(input)
+    }
+
+    pub fn target(&self) -> Option<Vec2> {
+        self.target
+    }
+
+    pub fn set_target(&mut self, target: Vec2) {
+        self.target = Some(target);
+    }
+
+    // Called once the player has arrived at the target.
+    pub fn clear_target(&mut self) {
+        self.target = None;
+    }
+
+    pub fn snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            pressed: Input::all()
+                .iter()
+                .copied()
+                .filter(|input| self.buttons.pressed(*input))
+                .collect(),
+            stick: self.stick,
+            target: self.target,
+        }
     }
+
+    // Makes the state match a snapshot, generating press and release edges as if the inputs had
+    // come from a device.
+    pub fn apply_snapshot(&mut self, snapshot: &InputSnapshot) {
+        for input in Input::all() {
+            if snapshot.pressed.contains(input) {
+                self.buttons.press(*input);
+            } else {
+                self.buttons.release(*input);
+            }
+        }
+        self.stick = snapshot.stick;
+        self.target = snapshot.target;
+    }
+
+    // Releases everything that's held; the releases show up as `just_released` this frame.
+    pub fn release_all(&mut self) {
+        self.buttons.release_all();
+        self.stick = Vec2::ZERO;
+        self.target = None;
+    }
+}
+
+// Everything held in `InputState` at one point in time, in a form that can be saved.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputSnapshot {
+    pub pressed: Vec<Input>,
+    pub stick: Vec2,
+    pub target: Option<Vec2>,
 }
 
-fn process_keyboard_input(mut state: ResMut<InputState>, mut keyboard: EventReader<KeyboardInput>) {
+// The keys bound to each action. These are user preferences, so they're read from and written to
+// a plain file rather than going through the asset server like the game config.
+#[derive(Debug, Clone, PartialEq, Resource, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputBindings {
+    keys: HashMap<Input, Vec<KeyCode>>,
+    #[serde(default)]
+    pub stick: StickSettings,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let keys = HashMap::from_iter([
+            (Input::MoveLeft, vec![KeyCode::KeyA]),
+            (Input::MoveRight, vec![KeyCode::KeyD]),
+            (Input::MoveUp, vec![KeyCode::KeyW]),
+            (Input::MoveDown, vec![KeyCode::KeyS]),
+        ]);
+        Self {
+            keys,
+            stick: default(),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn keys(&self, input: Input) -> &[KeyCode] {
+        self.keys.get(&input).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    // Returns the action that a key is bound to, if any.
+    pub fn action(&self, key: KeyCode) -> Option<Input> {
+        self.keys
+            .iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(input, _)| *input)
+    }
+
+    // Replaces the keys bound to an action with a single key. Fails with what else uses the key if
+    // it's already bound to another action or reserved for something that can't be rebound.
+    pub fn rebind(&mut self, input: Input, key: KeyCode) -> Result<(), RebindConflict> {
+        if let Some(reserved) = reserved_key(key) {
+            return Err(reserved);
+        }
+
+        match self.action(key) {
+            Some(other) if other != input => Err(RebindConflict::Action(other)),
+            _ => {
+                self.keys.insert(input, vec![key]);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn load(file: &BindingsFile) -> Result<Self, BindingsError> {
+        let bytes = fs::read(&file.0)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub fn save(&self, file: &BindingsFile) -> Result<(), BindingsError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        fs::write(&file.0, json)?;
+        Ok(())
+    }
+}
+
+// What's already using a key that an action couldn't be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RebindConflict {
+    Action(Input),
+    // Keys with a fixed use outside of gameplay, e.g. zooming the camera
+    Reserved(&'static str),
+}
+
+impl RebindConflict {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RebindConflict::Action(input) => input.label(),
+            RebindConflict::Reserved(label) => label,
+        }
+    }
+}
+
+fn reserved_key(key: KeyCode) -> Option<RebindConflict> {
+    let label = if ZOOM_IN_KEYS.contains(&key) {
+        "Zoom In"
+    } else if ZOOM_OUT_KEYS.contains(&key) {
+        "Zoom Out"
+    } else if RESET_ZOOM_KEYS.contains(&key) {
+        "Reset Zoom"
+    } else if key == MINIMAP_KEY {
+        "Minimap"
+    } else {
+        return None;
+    };
+    Some(RebindConflict::Reserved(label))
+}
+
+// How raw left stick positions are turned into movement.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StickSettings {
+    // Tilts shorter than this are ignored, so a worn stick doesn't make the goose drift.
+    pub dead_zone: f32,
+    // The remaining tilt is raised to this power; values above 1 give finer control near the
+    // center of the stick.
+    pub response_exponent: f32,
+}
+
+impl Default for StickSettings {
+    fn default() -> Self {
+        Self {
+            dead_zone: 0.15,
+            response_exponent: 1.5,
+        }
+    }
+}
+
+impl StickSettings {
+    pub fn apply(&self, raw: Vec2) -> Vec2 {
+        let magnitude = raw.length().min(1.);
+        if magnitude <= self.dead_zone || self.dead_zone >= 1. {
+            return Vec2::ZERO;
+        }
+
+        // Rescale so the response starts from zero at the edge of the dead-zone
+        let scaled = (magnitude - self.dead_zone) / (1. - self.dead_zone);
+        raw.normalize() * scaled.powf(self.response_exponent)
+    }
+}
+
+// Location of the user's bindings file
+#[derive(Debug, Clone, Resource)]
+pub struct BindingsFile(pub PathBuf);
+
+impl Default for BindingsFile {
+    fn default() -> Self {
+        Self(PathBuf::from("bindings.json"))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum BindingsError {
+    #[error("failed to parse")]
+    ParseError(#[from] serde_json::Error),
+    #[error("accessing bindings file")]
+    IoError(#[from] std::io::Error),
+}
+
+// Turns a key code into something friendlier to show the player, e.g. `KeyA` becomes `A`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    for prefix in ["Key", "Digit"] {
+        if let Some(short) = name.strip_prefix(prefix) {
+            return short.to_string();
+        }
+    }
+    name
+}
+
+fn load_bindings(file: Res<BindingsFile>, mut bindings: ResMut<InputBindings>) {
+    if !file.0.exists() {
+        return;
+    }
+
+    match InputBindings::load(&file) {
+        Ok(loaded) => *bindings = loaded,
+        Err(err) => {
+            error!(
+                "Unable to load bindings from {}; using defaults: {:?}",
+                file.0.display(),
+                err
+            );
+        }
+    }
+}
+
+fn clear_input_edges(mut state: ResMut<InputState>) {
+    state.buttons.clear();
+}
+
+// A key that's held while its binding changes would otherwise never see its release.
+fn release_inputs_on_rebind(bindings: Res<InputBindings>, mut state: ResMut<InputState>) {
+    if bindings.is_changed() {
+        state.release_all();
+    }
+}
+
+// Releases that happen while another window has focus are never delivered, so anything held when
+// focus is lost would otherwise stay held forever.
+fn release_inputs_on_focus_lost(
+    mut focus: EventReader<WindowFocused>,
+    mut occluded: EventReader<WindowOccluded>,
+    mut state: ResMut<InputState>,
+) {
+    let focus_lost = focus.read().any(|event| !event.focused);
+    let hidden = occluded.read().any(|event| event.occluded);
+
+    if focus_lost || hidden {
+        state.release_all();
+    }
+}
+
+fn process_keyboard_input(
+    bindings: Res<InputBindings>,
+    mut state: ResMut<InputState>,
+    mut keyboard: EventReader<KeyboardInput>,
+) {
     for event in keyboard.read() {
-        let input = match event.key_code {
-            KeyCode::KeyA => Input::MoveLeft,
-            KeyCode::KeyW => Input::MoveUp,
-            KeyCode::KeyD => Input::MoveRight,
-            KeyCode::KeyS => Input::MoveDown,
-            _ => continue,
+        let Some(input) = bindings.action(event.key_code) else {
+            continue;
         };
 
-        let pressed = match event.state {
-            ButtonState::Pressed => true,
-            ButtonState::Released => false,
-        };
+        match event.state {
+            ButtonState::Pressed => state.buttons.press(input),
+            ButtonState::Released => state.buttons.release(input),
+        }
+    }
+}
+
+// Reads every connected gamepad, so a controller plugged in mid-game is picked up as soon as bevy
+// registers it.
+fn process_gamepad_input(
+    bindings: Res<InputBindings>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut connections: EventReader<GamepadConnectionEvent>,
+    mut state: ResMut<InputState>,
+) {
+    for event in connections.read() {
+        // A disconnected gamepad never reports releases for whatever it was holding.
+        if let GamepadConnection::Disconnected = event.connection {
+            state.release_all();
+        }
+    }
+
+    let mut stick = Vec2::ZERO;
+
+    for gamepad in gamepads.iter() {
+        for input in Input::movement() {
+            let button = GamepadButton::new(gamepad, input.dpad_button());
+            if buttons.just_pressed(button) {
+                state.buttons.press(*input);
+            } else if buttons.just_released(button) {
+                state.buttons.release(*input);
+            }
+        }
+
+        let x = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX));
+        let y = axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY));
+        let raw = Vec2::new(x.unwrap_or_default(), y.unwrap_or_default());
+
+        // With several pads connected, whichever stick is tilted furthest wins.
+        let direction = bindings.stick.apply(raw);
+        if direction.length_squared() > stick.length_squared() {
+            stick = direction;
+        }
+    }
+
+    state.stick = stick;
+}
+
+// Holding the mouse button or a finger down keeps moving the target, so the player can steer by
+// dragging. Any keyboard or gamepad movement cancels it.
+fn process_pointer_input(
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    camera: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    interactions: Query<&Interaction>,
+    mut state: ResMut<InputState>,
+) {
+    if state.direction() != Vec2::ZERO {
+        state.target = None;
+        return;
+    }
+
+    // Clicks on buttons belong to the UI
+    if interactions.iter().any(|i| *i != Interaction::None) {
+        return;
+    }
+
+    let pointer = if mouse.pressed(MouseButton::Left) {
+        windows
+            .get_single()
+            .ok()
+            .and_then(|window| window.cursor_position())
+    } else {
+        touches.first_pressed_position()
+    };
+
+    let Some(pointer) = pointer else {
+        return;
+    };
+
+    let Ok((camera, camera_transform)) = camera.get_single() else {
+        return;
+    };
+
+    // The pointer is relative to the window, which can be bigger than the camera's viewport when
+    // it's letterboxed
+    let origin = camera
+        .logical_viewport_rect()
+        .map(|viewport| viewport.min)
+        .unwrap_or_default();
 
-        state.buttons.insert(input, pressed);
+    if let Some(target) = camera.viewport_to_world_2d(camera_transform, pointer - origin) {
+        state.target = Some(target);
     }
 }
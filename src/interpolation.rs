@@ -0,0 +1,54 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, restore_simulated_translation)
+            .add_systems(FixedLast, store_simulated_translation)
+            .add_systems(
+                PostUpdate,
+                interpolate_translation.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+// Smooths out movement that's simulated in `FixedUpdate`. Between ticks the `Transform` is drawn
+// part of the way from the previous tick's translation to the latest one, then put back before the
+// next tick so the simulation never sees the interpolated value.
+#[derive(Default, Debug, Component)]
+pub struct TransformInterpolation {
+    previous: Option<Vec3>,
+    current: Option<Vec3>,
+}
+
+fn restore_simulated_translation(mut query: Query<(&mut Transform, &TransformInterpolation)>) {
+    for (mut transform, interpolation) in &mut query {
+        if let Some(current) = interpolation.current {
+            transform.translation = current;
+        }
+    }
+}
+
+fn store_simulated_translation(mut query: Query<(&Transform, &mut TransformInterpolation)>) {
+    for (transform, mut interpolation) in &mut query {
+        // The first tick after spawning has nothing to interpolate from
+        interpolation.previous = interpolation.current.or(Some(transform.translation));
+        interpolation.current = Some(transform.translation);
+    }
+}
+
+fn interpolate_translation(
+    time: Res<Time<Fixed>>,
+    mut query: Query<(&mut Transform, &TransformInterpolation)>,
+) {
+    let alpha = time.overstep_fraction();
+
+    for (mut transform, interpolation) in &mut query {
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current)
+        else {
+            continue;
+        };
+        transform.translation = previous.lerp(current, alpha);
+    }
+}
@@ -0,0 +1,81 @@
+#![allow(clippy::type_complexity)]
+#![allow(clippy::too_many_arguments)]
+
+pub mod animation;
+pub mod aseprite;
+pub mod assets;
+pub mod autopilot;
+pub mod camera;
+pub mod enemy;
+pub mod feedback;
+pub mod game;
+pub mod ground;
+pub mod headless;
+pub mod indicators;
+pub mod input;
+pub mod interpolation;
+pub mod minimap;
+pub mod player;
+pub mod replay;
+pub mod settings;
+pub mod tween;
+pub mod ui;
+
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
+
+use crate::{
+    animation::AnimationPlugin, assets::AssetsPlugin, autopilot::AutopilotPlugin,
+    camera::CameraPlugin, enemy::EnemyPlugin, feedback::FeedbackPlugin, game::GamePlugin,
+    ground::GroundPlugin, indicators::IndicatorsPlugin, input::InputPlugin,
+    interpolation::InterpolationPlugin, minimap::MinimapPlugin, player::PlayerPlugin,
+    settings::SettingsPlugin, tween::TweenPlugin, ui::UiPlugin,
+};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, States)]
+pub enum GameState {
+    #[default]
+    Loading,
+    Gaming,
+    GameOver,
+}
+
+// Everything needed to play the game. Add this after bevy's `DefaultPlugins`, since the loading
+// state and our asset collections rely on the asset server.
+pub struct SuperWaddlePlugins;
+
+impl PluginGroup for SuperWaddlePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(GameStatePlugin)
+            .add(AnimationPlugin)
+            .add(AssetsPlugin)
+            .add(AutopilotPlugin)
+            .add(CameraPlugin)
+            .add(EnemyPlugin)
+            .add(FeedbackPlugin)
+            .add(GamePlugin)
+            .add(GroundPlugin)
+            .add(IndicatorsPlugin)
+            .add(InputPlugin)
+            .add(InterpolationPlugin)
+            .add(MinimapPlugin)
+            .add(PlayerPlugin)
+            .add(SettingsPlugin)
+            .add(TweenPlugin)
+            .add(UiPlugin)
+    }
+}
+
+// Sets up the states, and loads every plugin's assets before the game starts. This has to come
+// before the other plugins so they can add their assets to the loading state.
+struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        // We use a state to coordinate asset loading
+        app.init_state::<GameState>().add_loading_state(
+            LoadingState::new(GameState::Loading).continue_to_state(GameState::Gaming),
+        );
+    }
+}
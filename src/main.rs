@@ -1,35 +1,25 @@
-#![allow(clippy::type_complexity)]
-#![allow(clippy::too_many_arguments)]
-
-mod assets;
-mod camera;
-mod enemy;
-mod game;
-mod input;
-mod player;
-mod ui;
-
 use bevy::prelude::*;
-use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
-
-use crate::{
-    assets::AssetsPlugin, camera::CameraPlugin, enemy::EnemyPlugin, game::GamePlugin,
-    input::InputPlugin, player::PlayerPlugin, ui::UiPlugin,
+use super_waddle::{
+    autopilot::{Autopilot, Strategy},
+    headless::HeadlessArgs,
+    replay::{ReplayMode, ReplayPlugin},
+    SuperWaddlePlugins,
 };
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, States)]
-pub enum GameState {
-    #[default]
-    Loading,
-    Gaming,
-    GameOver,
-}
-
 fn main() {
-    let mut app = App::new();
+    // Let the autopilot play, with `--autopilot <nearest|route>`
+    let autopilot = match Strategy::from_args() {
+        Ok(strategy) => strategy,
+        Err(err) => exit_with_usage_error(err),
+    };
+
+    // Simulate a run without a window with `--headless <frames>`
+    if let Some(args) = HeadlessArgs::from_args(autopilot) {
+        args.run();
+        return;
+    }
 
-    // We use a state to coordinate asset loading
-    app.init_state::<GameState>();
+    let mut app = App::new();
 
     // We want pixel-perfect graphics so turn off anti-aliasing
     app.insert_resource(Msaa::Off);
@@ -37,22 +27,23 @@ fn main() {
     // Load default plugins first
     app.add_plugins(DefaultPlugins);
 
-    // Then configure the loading state
-    app.add_loading_state(
-        LoadingState::new(GameState::Loading).continue_to_state(GameState::Gaming),
-    );
-
-    // Then custom plugins
-    app.add_plugins((
-        AssetsPlugin,
-        CameraPlugin,
-        EnemyPlugin,
-        GamePlugin,
-        InputPlugin,
-        PlayerPlugin,
-        UiPlugin,
-    ));
+    // Then the game itself
+    app.add_plugins(SuperWaddlePlugins);
+
+    // Optionally record runs or replay a recording, with `--record <file>` or `--replay <file>`
+    app.add_plugins(ReplayPlugin {
+        mode: ReplayMode::from_args(),
+    });
+
+    if let Some(strategy) = autopilot {
+        Autopilot::enable(&mut app.world, strategy);
+    }
 
     // Run the game loop
     app.run();
 }
+
+fn exit_with_usage_error(err: String) -> ! {
+    eprintln!("{}", err);
+    std::process::exit(2);
+}
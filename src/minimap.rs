@@ -0,0 +1,250 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use crate::{
+    assets::GameConfig,
+    enemy::{Enemy, FlowerKind},
+    player::Player,
+    settings::SettingsState,
+    GameState,
+};
+
+// A map of the whole world in the corner of the screen, showing where the goose is and where the
+// flowers it hasn't picked yet are. It's drawn into a small image each frame.
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Minimap>()
+            .add_systems(OnEnter(GameState::Gaming), spawn_minimap)
+            .add_systems(OnExit(GameState::Gaming), cleanup_minimap)
+            .add_systems(
+                Update,
+                (
+                    toggle_minimap.run_if(in_state(SettingsState::Closed)),
+                    draw_minimap,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Gaming)),
+            );
+    }
+}
+
+// Shows and hides the minimap. It isn't rebindable, so no action can be bound to it either.
+pub const MINIMAP_KEY: KeyCode = KeyCode::KeyM;
+
+// How big the minimap is, and a color for each kind of marker
+#[derive(Debug, Clone, Resource)]
+pub struct Minimap {
+    pub visible: bool,
+    // Width and height in screen pixels
+    pub size: u32,
+    pub background: Color,
+    // The outline of the area flowers are spawned in
+    pub bounds: Color,
+    pub player: Color,
+    pub spike_flower: Color,
+}
+
+impl Minimap {
+    pub fn flower_color(&self, kind: FlowerKind) -> Color {
+        match kind {
+            FlowerKind::Spike => self.spike_flower,
+        }
+    }
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            size: 160,
+            background: Color::rgba(0., 0., 0., 0.6),
+            bounds: Color::rgb(0.8, 0.8, 0.8),
+            player: Color::WHITE,
+            spike_flower: Color::rgb(1., 0.55, 0.1),
+        }
+    }
+}
+
+// Marker component for the panel, which holds the image the map is drawn into
+#[derive(Component)]
+struct MinimapPanel(Handle<Image>);
+
+fn spawn_minimap(
+    mut commands: Commands,
+    settings: Res<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: settings.size,
+            height: settings.size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    // Keep the dots sharp if the panel ends up scaled
+    image.sampler = ImageSampler::nearest();
+    let image = images.add(image);
+
+    commands.spawn((
+        MinimapPanel(image.clone()),
+        ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(2.),
+                right: Val::Percent(2.),
+                width: Val::Px(settings.size as f32),
+                height: Val::Px(settings.size as f32),
+                display: display(settings.visible),
+                ..default()
+            },
+            image: UiImage::new(image),
+            ..default()
+        },
+    ));
+}
+
+fn cleanup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    panels: Query<(Entity, &MinimapPanel)>,
+) {
+    for (entity, panel) in &panels {
+        images.remove(&panel.0);
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn display(visible: bool) -> Display {
+    if visible {
+        Display::Flex
+    } else {
+        Display::None
+    }
+}
+
+fn toggle_minimap(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Minimap>,
+    mut panels: Query<&mut Style, With<MinimapPanel>>,
+) {
+    if !keyboard.just_pressed(MINIMAP_KEY) {
+        return;
+    }
+
+    settings.visible = !settings.visible;
+    for mut style in &mut panels {
+        style.display = display(settings.visible);
+    }
+}
+
+fn draw_minimap(
+    config: GameConfig,
+    settings: Res<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    panels: Query<&MinimapPanel>,
+    player: Query<&Transform, With<Player>>,
+    flowers: Query<(&Transform, &FlowerKind), With<Enemy>>,
+) {
+    if !settings.visible {
+        return;
+    }
+
+    let Some(config) = config.get() else {
+        return;
+    };
+
+    for panel in &panels {
+        let Some(image) = images.get_mut(&panel.0) else {
+            continue;
+        };
+
+        let mut canvas = Canvas::new(image, config.world_size);
+        canvas.clear(settings.background);
+        canvas.outline(settings.bounds);
+
+        for (flower, kind) in &flowers {
+            let color = settings.flower_color(*kind);
+            canvas.dot(flower.translation.truncate(), 1, color);
+        }
+
+        // Drawn last so it's never hidden under a flower
+        for player in &player {
+            canvas.dot(player.translation.truncate(), 2, settings.player);
+        }
+    }
+}
+
+// Draws into a square image that covers the world from -world_size to world_size on both axes.
+struct Canvas<'a> {
+    image: &'a mut Image,
+    size: i32,
+    world_size: f32,
+}
+
+impl<'a> Canvas<'a> {
+    fn new(image: &'a mut Image, world_size: f32) -> Self {
+        let size = image.width().min(image.height()) as i32;
+        Self {
+            image,
+            size,
+            world_size,
+        }
+    }
+
+    fn clear(&mut self, color: Color) {
+        let color = color.as_rgba_u8();
+        for pixel in self.image.data.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&color);
+        }
+    }
+
+    fn outline(&mut self, color: Color) {
+        let last = self.size - 1;
+        for i in 0..self.size {
+            self.set(i, 0, color);
+            self.set(i, last, color);
+            self.set(0, i, color);
+            self.set(last, i, color);
+        }
+    }
+
+    // A square `radius` pixels out from the center. Anything outside the world is drawn at its
+    // edge, so the goose doesn't vanish if it wanders off.
+    fn dot(&mut self, position: Vec2, radius: i32, color: Color) {
+        let (x, y) = self.to_pixel(position);
+        for dy in -radius..radius {
+            for dx in -radius..radius {
+                self.set(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    fn to_pixel(&self, position: Vec2) -> (i32, i32) {
+        let normalized = (position / self.world_size.max(f32::EPSILON) + 1.) / 2.;
+        let pixel =
+            (normalized * self.size as f32).clamp(Vec2::ZERO, Vec2::splat(self.size as f32 - 1.));
+        // Images go down from the top, the world goes up from the bottom
+        (pixel.x as i32, self.size - 1 - pixel.y as i32)
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: Color) {
+        if !(0..self.size).contains(&x) || !(0..self.size).contains(&y) {
+            return;
+        }
+
+        let start = ((y as u32 * self.image.width() + x as u32) * 4) as usize;
+        self.image.data[start..start + 4].copy_from_slice(&color.as_rgba_u8());
+    }
+}
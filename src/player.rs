@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
 use bevy_asset_loader::{
     asset_collection::AssetCollection,
@@ -8,10 +10,13 @@ use bevy_asset_loader::{
 };
 
 use crate::{
+    animation::{SpriteAnimation, SpriteSheet},
     assets::GameConfig,
     enemy::{EnemySet, PickFlower},
     game::GameObject,
     input::{InputSet, InputState},
+    interpolation::TransformInterpolation,
+    tween::{Animator, Ease, ScaleLens, Tween},
     GameState,
 };
 
@@ -24,12 +29,19 @@ impl Plugin for PlayerPlugin {
         )
         .add_systems(OnEnter(GameState::Gaming), spawn_player)
         .add_systems(
-            Update,
+            FixedUpdate,
             (
-                move_player.after(InputSet::ReadInput),
+                move_player
+                    .after(InputSet::ReadInput)
+                    .before(EnemySet::Collisions),
                 count_picked_flowers.after(EnemySet::Collisions),
             )
                 .run_if(in_state(GameState::Gaming)),
+        )
+        // Both follow where `move_player` left the goose in the ticks before this frame
+        .add_systems(
+            Update,
+            (animate_player, turn_player).run_if(in_state(GameState::Gaming)),
         );
     }
 }
@@ -40,48 +52,101 @@ pub struct Player;
 #[derive(Debug, Component, Deref, DerefMut)]
 pub struct FlowerCount(pub i32);
 
+// Where the player was at the start of this tick, so collisions can check the whole path they
+// moved along rather than just where they ended up.
+#[derive(Default, Debug, Component, Deref)]
+pub struct PreviousPosition(Vec2);
+
+// The direction the goose last moved in, as a unit vector. It keeps facing that way while standing
+// still.
+#[derive(Debug, Clone, Copy, Component, Deref)]
+pub struct Facing(Vec2);
+
+impl Default for Facing {
+    // The goose is drawn looking left
+    fn default() -> Self {
+        Self(Vec2::NEG_X)
+    }
+}
+
+// The x scale the sprite is turning towards, or has finished turning to. It's kept on the goose so
+// a new one starts over when a game is retried.
+#[derive(Debug, Component)]
+struct TurningTo(f32);
+
+impl Default for TurningTo {
+    // Facing the way the goose is drawn
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+// How long the goose takes to turn around
+const TURN_TIME: Duration = Duration::from_millis(150);
+
 #[derive(AssetCollection, Resource)]
 struct PlayerAssets {
-    #[asset(path = "sprites/goose.png")]
-    goose: Handle<Image>,
+    #[asset(path = "sprites/goose.aseprite")]
+    goose: Handle<SpriteSheet>,
 }
 
-fn spawn_player(mut commands: Commands, assets: Res<PlayerAssets>) {
-    commands.spawn((
+// Sprites aren't loaded when running headless, so the player is spawned without one.
+fn spawn_player(mut commands: Commands, assets: Option<Res<PlayerAssets>>) {
+    let mut player = commands.spawn((
         Player,
         FlowerCount(0),
         GameObject,
-        SpriteBundle {
-            texture: assets.goose.clone(),
-            ..default()
-        },
+        TransformInterpolation::default(),
+        PreviousPosition::default(),
+        Facing::default(),
+        TurningTo::default(),
+        SpriteSheetBundle::default(),
     ));
+
+    if let Some(assets) = assets {
+        player.insert(SpriteAnimation::repeating(assets.goose.clone(), "Idle"));
+    }
 }
 
 fn move_player(
     config: GameConfig,
     time: Res<Time>,
-    input: Res<InputState>,
-    mut player: Query<&mut Transform, With<Player>>,
+    mut input: ResMut<InputState>,
+    mut player: Query<(&mut Transform, &mut PreviousPosition, &mut Facing), With<Player>>,
 ) {
     let Some(config) = config.get() else {
         return;
     };
 
-    let Ok(mut player) = player.get_single_mut() else {
+    let Ok((mut player, mut previous, mut facing)) = player.get_single_mut() else {
         return;
     };
 
-    let direction = input.normalized_direction();
+    previous.0 = player.translation.truncate();
+
+    let step = config.player_move_speed * time.delta_seconds();
+
+    let mut direction = input.direction();
     if direction == Vec2::ZERO {
-        return;
+        // Walk toward the clicked point, stopping once we'd reach it this frame.
+        let Some(target) = input.target() else {
+            return;
+        };
+
+        let offset = target - player.translation.truncate();
+        if offset.length() <= step {
+            player.translation = target.extend(player.translation.z);
+            input.clear_target();
+            return;
+        }
+
+        direction = offset.normalize();
     }
 
-    let transform = player.mul_transform(Transform::from_translation(
-        (direction * config.player_move_speed * time.delta_seconds()).extend(0.0f32),
-    ));
+    facing.0 = direction.normalize();
 
-    *player = transform;
+    // Added directly rather than through the transform, which is flipped when facing right
+    player.translation += (direction * step).extend(0.);
 }
 
 fn count_picked_flowers(
@@ -95,3 +160,45 @@ fn count_picked_flowers(
         player.0 += 1;
     }
 }
+
+// Waddles whenever there's somewhere to go, whether from the keys, a stick or a clicked target.
+fn animate_player(input: Res<InputState>, mut player: Query<&mut SpriteAnimation, With<Player>>) {
+    let Ok(mut animation) = player.get_single_mut() else {
+        return;
+    };
+
+    let walking = input.direction() != Vec2::ZERO || input.target().is_some();
+    animation.play(if walking { "Walk" } else { "Idle" });
+}
+
+// Flips the sprite to face the way the goose is walking, squashing it through the middle so it
+// turns rather than snapping round.
+fn turn_player(
+    mut commands: Commands,
+    mut player: Query<(Entity, &Facing, &mut TurningTo, &Transform), With<Player>>,
+) {
+    let Ok((entity, facing, mut turning_to, transform)) = player.get_single_mut() else {
+        return;
+    };
+
+    // Walking straight up or down keeps whichever way it was already looking
+    let target = match facing.x {
+        x if x > 0. => -1.,
+        x if x < 0. => 1.,
+        _ => return,
+    };
+
+    if turning_to.0 == target {
+        return;
+    }
+    turning_to.0 = target;
+
+    commands.entity(entity).insert(Animator::new(Tween::new(
+        Ease::QuadInOut,
+        TURN_TIME,
+        ScaleLens {
+            start: transform.scale,
+            end: Vec3::new(target, 1., 1.),
+        },
+    )));
+}
@@ -0,0 +1,231 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, utils::thiserror::Error};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::{GameFrame, GameRng, SeedOverride},
+    input::{InputSet, InputSnapshot, InputSource, InputState},
+    GameState,
+};
+
+#[derive(Debug, Clone, Default)]
+pub enum ReplayMode {
+    #[default]
+    Off,
+    // Saves the input of each run to this file when the run ends
+    Record(PathBuf),
+    // Feeds the input saved in this file back in, instead of reading devices
+    Playback(PathBuf),
+}
+
+impl ReplayMode {
+    // Picks the mode from `--record <file>` or `--replay <file>` on the command line.
+    pub fn from_args() -> Self {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match (arg.as_str(), args.next()) {
+                ("--record", Some(path)) => return ReplayMode::Record(path.into()),
+                ("--replay", Some(path)) => return ReplayMode::Playback(path.into()),
+                _ => {}
+            }
+        }
+        ReplayMode::Off
+    }
+}
+
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            ReplayMode::Off => {}
+            ReplayMode::Record(path) => {
+                app.insert_resource(InputRecorder::new(path.clone()))
+                    .add_systems(OnEnter(GameState::Gaming), start_recording)
+                    .add_systems(OnExit(GameState::Gaming), save_recording)
+                    .add_systems(
+                        FixedUpdate,
+                        record_input
+                            .after(InputSet::ReadInput)
+                            .run_if(in_state(GameState::Gaming)),
+                    );
+            }
+            ReplayMode::Playback(path) => {
+                let recording = match Recording::load(path) {
+                    Ok(recording) => recording,
+                    Err(err) => {
+                        error!(
+                            "Unable to load recording from {}; playing normally: {:?}",
+                            path.display(),
+                            err
+                        );
+                        return;
+                    }
+                };
+
+                info!(
+                    "Replaying {} input changes from {}",
+                    recording.frames.len(),
+                    path.display()
+                );
+
+                app.insert_resource(InputSource::Playback)
+                    .insert_resource(SeedOverride(Some(recording.seed)))
+                    .insert_resource(InputPlayback::new(recording))
+                    .add_systems(OnEnter(GameState::Gaming), restart_playback)
+                    .add_systems(
+                        FixedUpdate,
+                        play_back_input
+                            .in_set(InputSet::ReadInput)
+                            .run_if(in_state(GameState::Gaming)),
+                    );
+            }
+        }
+    }
+}
+
+// The input for one run: the seed it was played with, and every change to `InputState` along with
+// the frame it happened on.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recording {
+    pub seed: u64,
+    pub frames: Vec<RecordedInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedInput {
+    pub frame: u64,
+    pub input: InputSnapshot,
+}
+
+impl Recording {
+    pub fn load(path: &Path) -> Result<Self, RecordingError> {
+        let bytes = fs::read(path)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), RecordingError> {
+        let json = serde_json::to_vec(self)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("failed to parse")]
+    ParseError(#[from] serde_json::Error),
+    #[error("accessing recording file")]
+    IoError(#[from] std::io::Error),
+}
+
+#[derive(Debug, Resource)]
+struct InputRecorder {
+    path: PathBuf,
+    recording: Recording,
+    last: InputSnapshot,
+}
+
+impl InputRecorder {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            recording: default(),
+            last: default(),
+        }
+    }
+}
+
+fn start_recording(mut recorder: ResMut<InputRecorder>) {
+    recorder.recording = default();
+    recorder.last = default();
+}
+
+fn record_input(
+    frame: Res<GameFrame>,
+    state: Res<InputState>,
+    mut recorder: ResMut<InputRecorder>,
+) {
+    let snapshot = state.snapshot();
+    if snapshot == recorder.last {
+        return;
+    }
+
+    recorder.recording.frames.push(RecordedInput {
+        frame: **frame,
+        input: snapshot.clone(),
+    });
+    recorder.last = snapshot;
+}
+
+fn save_recording(mut recorder: ResMut<InputRecorder>, rng: Option<Res<GameRng>>) {
+    let Some(rng) = rng else {
+        error!("unable to get the seed for this run; not saving recording");
+        return;
+    };
+
+    recorder.recording.seed = rng.seed();
+
+    match recorder.recording.save(&recorder.path) {
+        Ok(()) => info!(
+            "Saved {} input changes to {}",
+            recorder.recording.frames.len(),
+            recorder.path.display()
+        ),
+        Err(err) => error!(
+            "Unable to save recording to {}: {:?}",
+            recorder.path.display(),
+            err
+        ),
+    }
+}
+
+#[derive(Debug, Resource)]
+struct InputPlayback {
+    recording: Recording,
+    // Index of the next recorded change to apply
+    cursor: usize,
+}
+
+impl InputPlayback {
+    fn new(recording: Recording) -> Self {
+        Self {
+            recording,
+            cursor: 0,
+        }
+    }
+}
+
+// Every retry plays the same recording again from the start.
+fn restart_playback(mut playback: ResMut<InputPlayback>, mut state: ResMut<InputState>) {
+    playback.cursor = 0;
+    state.apply_snapshot(&InputSnapshot::default());
+}
+
+fn play_back_input(
+    frame: Res<GameFrame>,
+    mut playback: ResMut<InputPlayback>,
+    mut state: ResMut<InputState>,
+) {
+    let playback = &mut *playback;
+    while let Some(recorded) = playback.recording.frames.get(playback.cursor) {
+        if recorded.frame > **frame {
+            break;
+        }
+
+        state.apply_snapshot(&recorded.input);
+        playback.cursor += 1;
+
+        if playback.cursor == playback.recording.frames.len() {
+            info!("Reached the end of the recording on frame {}", **frame);
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+};
+
+use crate::{
+    camera::ScreenShake,
+    input::{key_label, BindingsFile, Input, InputBindings, InputSet, InputState, RebindConflict},
+    ui::{spawn_button, UiAssets},
+    GameState,
+};
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash, States)]
+pub enum SettingsState {
+    #[default]
+    Closed,
+    Open,
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<SettingsState>()
+            .init_resource::<PendingRebind>()
+            // Gameplay input is ignored while the player is fiddling with their bindings, whether
+            // it's read every frame or every tick.
+            .configure_sets(
+                PreUpdate,
+                InputSet::ReadInput.run_if(in_state(SettingsState::Closed)),
+            )
+            .configure_sets(
+                FixedUpdate,
+                InputSet::ReadInput.run_if(in_state(SettingsState::Closed)),
+            )
+            .add_systems(
+                OnEnter(SettingsState::Open),
+                (spawn_settings_ui, release_inputs),
+            )
+            .add_systems(OnExit(SettingsState::Open), cleanup_settings_ui)
+            .add_systems(
+                Update,
+                (toggle_settings, capture_rebind_key)
+                    .chain()
+                    .run_if(not(in_state(GameState::Loading))),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_settings_buttons,
+                    update_binding_text,
+                    update_screen_shake_text,
+                    update_status_text,
+                )
+                    .run_if(in_state(SettingsState::Open)),
+            );
+    }
+}
+
+// The action waiting for its new key, if the player has pressed one of the rebind buttons, and the
+// last key they tried that was already taken by another action.
+#[derive(Default, Debug, Resource)]
+struct PendingRebind {
+    action: Option<Input>,
+    conflict: Option<(KeyCode, RebindConflict)>,
+}
+
+impl PendingRebind {
+    fn start(&mut self, input: Input) {
+        self.action = Some(input);
+        self.conflict = None;
+    }
+
+    fn finish(&mut self) {
+        self.action = None;
+        self.conflict = None;
+    }
+}
+
+// Marker component that makes it easy to clean up the settings screen when it's closed.
+#[derive(Component)]
+struct SettingsUi;
+
+// Marker component for the text that lists the keys bound to an action
+#[derive(Component)]
+struct BindingText(Input);
+
+// Marker component for the text that says whether screen shake is on
+#[derive(Component)]
+struct ScreenShakeText;
+
+// Marker component for the text that prompts the player and reports conflicts
+#[derive(Component)]
+struct SettingsStatusText;
+
+#[derive(Component)]
+enum SettingsButtonChoice {
+    Rebind(Input),
+    ToggleScreenShake,
+    ResetDefaults,
+    Close,
+}
+
+fn spawn_settings_ui(
+    mut commands: Commands,
+    bindings: Res<InputBindings>,
+    screen_shake: Res<ScreenShake>,
+    assets: Res<UiAssets>,
+) {
+    let text_style = TextStyle {
+        font: assets.font.clone(),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            SettingsUi,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(8.),
+                    padding: UiRect::all(Val::Px(20.)),
+                    top: Val::Percent(15.),
+                    left: Val::Percent(30.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.85).into(),
+                // Draw on top of the HUD and game over UI
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Controls",
+                TextStyle {
+                    font: assets.font.clone(),
+                    font_size: 30.,
+                    ..default()
+                },
+            ));
+
+            // One row per action: the current keys, then a button to rebind them
+            for input in Input::all() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            justify_content: JustifyContent::SpaceBetween,
+                            column_gap: Val::Px(20.),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            BindingText(*input),
+                            TextBundle::from_section(
+                                binding_text(&bindings, *input),
+                                text_style.clone(),
+                            ),
+                        ));
+
+                        spawn_button(parent)
+                            .insert(SettingsButtonChoice::Rebind(*input))
+                            .with_children(|parent| {
+                                parent
+                                    .spawn(TextBundle::from_section("Rebind", text_style.clone()));
+                            });
+                    });
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::SpaceBetween,
+                        column_gap: Val::Px(20.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        ScreenShakeText,
+                        TextBundle::from_section(
+                            screen_shake_text(&screen_shake),
+                            text_style.clone(),
+                        ),
+                    ));
+
+                    spawn_button(parent)
+                        .insert(SettingsButtonChoice::ToggleScreenShake)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Toggle", text_style.clone()));
+                        });
+                });
+
+            parent.spawn((
+                SettingsStatusText,
+                TextBundle::from_section("", text_style.clone()),
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        column_gap: Val::Px(20.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    spawn_button(parent)
+                        .insert(SettingsButtonChoice::ResetDefaults)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Reset to defaults",
+                                text_style.clone(),
+                            ));
+                        });
+
+                    spawn_button(parent)
+                        .insert(SettingsButtonChoice::Close)
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("Close", text_style.clone()));
+                        });
+                });
+        });
+}
+
+fn binding_text(bindings: &InputBindings, input: Input) -> String {
+    let keys = bindings
+        .keys(input)
+        .iter()
+        .map(|key| key_label(*key))
+        .collect::<Vec<_>>();
+
+    if keys.is_empty() {
+        format!("{}: (unbound)", input.label())
+    } else {
+        format!("{}: {}", input.label(), keys.join(", "))
+    }
+}
+
+fn screen_shake_text(screen_shake: &ScreenShake) -> String {
+    let state = if screen_shake.enabled { "on" } else { "off" };
+    format!("Screen shake: {}", state)
+}
+
+fn cleanup_settings_ui(
+    mut commands: Commands,
+    mut pending: ResMut<PendingRebind>,
+    ui: Query<Entity, With<SettingsUi>>,
+) {
+    pending.finish();
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn release_inputs(mut state: ResMut<InputState>) {
+    state.release_all();
+}
+
+fn toggle_settings(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    pending: Res<PendingRebind>,
+    state: Res<State<SettingsState>>,
+    mut next: ResMut<NextState<SettingsState>>,
+) {
+    // While waiting for a key, escape cancels the rebind instead of closing the screen.
+    if pending.action.is_some() || !keyboard.just_pressed(KeyCode::Escape) {
+        return;
+    }
+
+    match state.get() {
+        SettingsState::Closed => next.set(SettingsState::Open),
+        SettingsState::Open => next.set(SettingsState::Closed),
+    }
+}
+
+fn capture_rebind_key(
+    file: Res<BindingsFile>,
+    mut pending: ResMut<PendingRebind>,
+    mut bindings: ResMut<InputBindings>,
+    mut keyboard: EventReader<KeyboardInput>,
+) {
+    for event in keyboard.read() {
+        let Some(input) = pending.action else {
+            continue;
+        };
+
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        if event.key_code == KeyCode::Escape {
+            pending.finish();
+            continue;
+        }
+
+        match bindings.rebind(input, event.key_code) {
+            Ok(()) => {
+                pending.finish();
+                save_bindings(&bindings, &file);
+            }
+            Err(other) => {
+                // Keep listening so the player can pick a different key.
+                pending.conflict = Some((event.key_code, other));
+            }
+        }
+    }
+}
+
+fn save_bindings(bindings: &InputBindings, file: &BindingsFile) {
+    if let Err(err) = bindings.save(file) {
+        error!("Unable to save bindings to {}: {:?}", file.0.display(), err);
+    }
+}
+
+fn handle_settings_buttons(
+    buttons: Query<(&Interaction, &SettingsButtonChoice), Changed<Interaction>>,
+    file: Res<BindingsFile>,
+    mut pending: ResMut<PendingRebind>,
+    mut bindings: ResMut<InputBindings>,
+    mut screen_shake: ResMut<ScreenShake>,
+    mut next: ResMut<NextState<SettingsState>>,
+) {
+    for (interaction, choice) in &buttons {
+        if let Interaction::Pressed = interaction {
+            match choice {
+                SettingsButtonChoice::Rebind(input) => {
+                    pending.start(*input);
+                }
+                SettingsButtonChoice::ToggleScreenShake => {
+                    screen_shake.enabled = !screen_shake.enabled;
+                }
+                SettingsButtonChoice::ResetDefaults => {
+                    pending.finish();
+                    *bindings = InputBindings::default();
+                    save_bindings(&bindings, &file);
+                }
+                SettingsButtonChoice::Close => {
+                    next.set(SettingsState::Closed);
+                }
+            }
+        }
+    }
+}
+
+fn update_binding_text(bindings: Res<InputBindings>, mut texts: Query<(&mut Text, &BindingText)>) {
+    if !bindings.is_changed() {
+        return;
+    }
+
+    for (mut text, binding) in &mut texts {
+        text.sections[0].value = binding_text(&bindings, binding.0);
+    }
+}
+
+fn update_screen_shake_text(
+    screen_shake: Res<ScreenShake>,
+    mut texts: Query<&mut Text, With<ScreenShakeText>>,
+) {
+    if !screen_shake.is_changed() {
+        return;
+    }
+
+    for mut text in &mut texts {
+        text.sections[0].value = screen_shake_text(&screen_shake);
+    }
+}
+
+fn update_status_text(
+    pending: Res<PendingRebind>,
+    mut status: Query<&mut Text, With<SettingsStatusText>>,
+) {
+    let Ok(mut status) = status.get_single_mut() else {
+        return;
+    };
+
+    let message = match (pending.action, pending.conflict) {
+        (Some(_), Some((key, other))) => format!(
+            "{} is already bound to {}; press another key (Esc to cancel)",
+            key_label(key),
+            other.label()
+        ),
+        (Some(input), None) => format!("Press a key for {} (Esc to cancel)", input.label()),
+        (None, _) => String::new(),
+    };
+
+    if status.sections[0].value != message {
+        status.sections[0].value = message;
+    }
+}
@@ -0,0 +1,319 @@
+use std::{collections::VecDeque, f32::consts::PI, time::Duration};
+
+use bevy::{prelude::*, transform::TransformSystem, ui::UiSystem};
+
+// Moves a component's values from one state to another over time, e.g. sliding a sprite across
+// the screen or fading text out. Add an `Animator<C>` holding one or more `Tween`s to an entity
+// with the component `C` and they're played one after the other.
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TweenCompleted>().add_systems(
+            PostUpdate,
+            (
+                animate::<Transform>,
+                animate::<Sprite>,
+                animate::<Text>,
+                animate::<Style>,
+            )
+                .before(TransformSystem::TransformPropagate)
+                .before(UiSystem::Layout),
+        );
+    }
+}
+
+// Sent when a tween made with `Tween::with_completed_event` finishes.
+#[derive(Debug, Clone, Copy, Event)]
+pub struct TweenCompleted {
+    pub entity: Entity,
+    pub id: u64,
+}
+
+// How a tween moves between its start and end, see https://easings.net
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    SineInOut,
+    // Overshoots the end a little, then settles back
+    BackOut,
+    BounceOut,
+}
+
+impl Ease {
+    // Maps how far through the tween we are (0 to 1) to how far between start and end to be.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0., 1.);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1. - (1. - t) * (1. - t),
+            Ease::QuadInOut if t < 0.5 => 2. * t * t,
+            Ease::QuadInOut => 1. - (-2. * t + 2.).powi(2) / 2.,
+            Ease::CubicIn => t * t * t,
+            Ease::CubicOut => 1. - (1. - t).powi(3),
+            Ease::CubicInOut if t < 0.5 => 4. * t * t * t,
+            Ease::CubicInOut => 1. - (-2. * t + 2.).powi(3) / 2.,
+            Ease::SineInOut => -((PI * t).cos() - 1.) / 2.,
+            Ease::BackOut => {
+                const OVERSHOOT: f32 = 1.70158;
+                1. + (OVERSHOOT + 1.) * (t - 1.).powi(3) + OVERSHOOT * (t - 1.).powi(2)
+            }
+            Ease::BounceOut => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1. / D {
+                    N * t * t
+                } else if t < 2. / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+// Sets the part of a component a tween animates, `ratio` of the way from start to end. The ratio
+// can go a little outside 0 to 1 with easing that overshoots.
+pub trait Lens<C>: Send + Sync + 'static {
+    fn lerp(&mut self, target: &mut C, ratio: f32);
+}
+
+pub struct TranslationLens {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl Lens<Transform> for TranslationLens {
+    fn lerp(&mut self, target: &mut Transform, ratio: f32) {
+        target.translation = self.start.lerp(self.end, ratio);
+    }
+}
+
+pub struct ScaleLens {
+    pub start: Vec3,
+    pub end: Vec3,
+}
+
+impl Lens<Transform> for ScaleLens {
+    fn lerp(&mut self, target: &mut Transform, ratio: f32) {
+        target.scale = self.start.lerp(self.end, ratio);
+    }
+}
+
+pub struct RotationLens {
+    pub start: Quat,
+    pub end: Quat,
+}
+
+impl Lens<Transform> for RotationLens {
+    fn lerp(&mut self, target: &mut Transform, ratio: f32) {
+        target.rotation = self.start.slerp(self.end, ratio);
+    }
+}
+
+pub struct SpriteColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<Sprite> for SpriteColorLens {
+    fn lerp(&mut self, target: &mut Sprite, ratio: f32) {
+        target.color = lerp_color(self.start, self.end, ratio);
+    }
+}
+
+// Colors every section of the text the same
+pub struct TextColorLens {
+    pub start: Color,
+    pub end: Color,
+}
+
+impl Lens<Text> for TextColorLens {
+    fn lerp(&mut self, target: &mut Text, ratio: f32) {
+        let color = lerp_color(self.start, self.end, ratio);
+        for section in &mut target.sections {
+            section.style.color = color;
+        }
+    }
+}
+
+// Moves a UI node by its `left`, `right`, `top` and `bottom`
+pub struct UiPositionLens {
+    pub start: UiRect,
+    pub end: UiRect,
+}
+
+impl Lens<Style> for UiPositionLens {
+    fn lerp(&mut self, target: &mut Style, ratio: f32) {
+        target.left = lerp_val(self.start.left, self.end.left, ratio);
+        target.right = lerp_val(self.start.right, self.end.right, ratio);
+        target.top = lerp_val(self.start.top, self.end.top, ratio);
+        target.bottom = lerp_val(self.start.bottom, self.end.bottom, ratio);
+    }
+}
+
+// Values in different units can't be blended, so they jump to the end at the halfway point.
+fn lerp_val(start: Val, end: Val, ratio: f32) -> Val {
+    let lerp = |start: f32, end: f32| start + (end - start) * ratio;
+    match (start, end) {
+        (Val::Px(start), Val::Px(end)) => Val::Px(lerp(start, end)),
+        (Val::Percent(start), Val::Percent(end)) => Val::Percent(lerp(start, end)),
+        (Val::Vw(start), Val::Vw(end)) => Val::Vw(lerp(start, end)),
+        (Val::Vh(start), Val::Vh(end)) => Val::Vh(lerp(start, end)),
+        (Val::VMin(start), Val::VMin(end)) => Val::VMin(lerp(start, end)),
+        (Val::VMax(start), Val::VMax(end)) => Val::VMax(lerp(start, end)),
+        _ if ratio < 0.5 => start,
+        _ => end,
+    }
+}
+
+fn lerp_color(start: Color, end: Color, ratio: f32) -> Color {
+    let start = Vec4::from(start.as_rgba_f32());
+    let end = Vec4::from(end.as_rgba_f32());
+    let [r, g, b, a] = start.lerp(end, ratio).to_array();
+    Color::rgba(r, g, b, a)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    Times(u32),
+    Forever,
+}
+
+pub struct Tween<C> {
+    lens: Box<dyn Lens<C>>,
+    duration: Duration,
+    ease: Ease,
+    repeat: Repeat,
+    // Whether every other repeat plays backwards
+    ping_pong: bool,
+    completed_event: Option<u64>,
+    elapsed: Duration,
+    played: u32,
+}
+
+impl<C: 'static> Tween<C> {
+    pub fn new(ease: Ease, duration: Duration, lens: impl Lens<C>) -> Self {
+        Self {
+            lens: Box::new(lens),
+            duration,
+            ease,
+            repeat: Repeat::Times(1),
+            ping_pong: false,
+            completed_event: None,
+            elapsed: Duration::ZERO,
+            played: 0,
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    // Plays back to the start after reaching the end, counting each way as one repeat.
+    pub fn with_ping_pong(mut self) -> Self {
+        self.ping_pong = true;
+        self
+    }
+
+    // Sends `TweenCompleted` with this id once the tween has played through.
+    pub fn with_completed_event(mut self, id: u64) -> Self {
+        self.completed_event = Some(id);
+        self
+    }
+
+    // Moves the tween on by `delta`. Once it's finished, returns how much of `delta` was left over
+    // for whatever plays next.
+    fn tick(&mut self, delta: Duration, target: &mut C) -> Option<Duration> {
+        if self.duration.is_zero() {
+            self.lens.lerp(target, self.ease.apply(1.));
+            return Some(delta);
+        }
+
+        self.elapsed += delta;
+        while self.elapsed >= self.duration {
+            if let Repeat::Times(times) = self.repeat {
+                if self.played + 1 >= times {
+                    let left_over = self.elapsed - self.duration;
+                    self.elapsed = self.duration;
+                    self.apply(target);
+                    return Some(left_over);
+                }
+            }
+            self.elapsed -= self.duration;
+            self.played = self.played.wrapping_add(1);
+        }
+
+        self.apply(target);
+        None
+    }
+
+    fn apply(&mut self, target: &mut C) {
+        let progress = self.elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        let backwards = self.ping_pong && self.played % 2 == 1;
+        let progress = if backwards { 1. - progress } else { progress };
+        self.lens.lerp(target, self.ease.apply(progress));
+    }
+}
+
+// Plays its tweens on the entity's `C` component in order, and is removed once they're all done.
+#[derive(Component)]
+pub struct Animator<C: Component> {
+    tweens: VecDeque<Tween<C>>,
+}
+
+impl<C: Component> Animator<C> {
+    pub fn new(tween: Tween<C>) -> Self {
+        Self {
+            tweens: VecDeque::from([tween]),
+        }
+    }
+
+    // Plays another tween once the ones before it are done.
+    pub fn then(mut self, tween: Tween<C>) -> Self {
+        self.tweens.push_back(tween);
+        self
+    }
+}
+
+fn animate<C: Component>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Animator<C>, &mut C)>,
+    mut completed: EventWriter<TweenCompleted>,
+) {
+    for (entity, mut animator, mut target) in &mut query {
+        let mut delta = time.delta();
+
+        while let Some(tween) = animator.tweens.front_mut() {
+            let Some(left_over) = tween.tick(delta, &mut target) else {
+                break;
+            };
+
+            if let Some(id) = tween.completed_event {
+                completed.send(TweenCompleted { entity, id });
+            }
+            animator.tweens.pop_front();
+            delta = left_over;
+        }
+
+        if animator.tweens.is_empty() {
+            commands.entity(entity).remove::<Animator<C>>();
+        }
+    }
+}
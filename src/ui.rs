@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use bevy::{app::AppExit, ecs::system::EntityCommands, prelude::*};
 use bevy_asset_loader::{
     asset_collection::AssetCollection,
@@ -10,6 +12,7 @@ use bevy_asset_loader::{
 use crate::{
     game::GameTimer,
     player::{FlowerCount, Player},
+    tween::{Animator, Ease, ScaleLens, Tween, UiPositionLens},
     GameState,
 };
 
@@ -36,9 +39,9 @@ impl Plugin for UiPlugin {
 }
 
 #[derive(AssetCollection, Resource)]
-struct UiAssets {
+pub(crate) struct UiAssets {
     #[asset(path = "fonts/RobotoMono-Regular.ttf")]
-    font: Handle<Font>,
+    pub font: Handle<Font>,
 }
 
 // Marker component that makes it easy to clean up this UI when it's no longer needed.
@@ -47,13 +50,19 @@ struct GameUi;
 
 // Marker component for the text bundle that shows the flower count
 #[derive(Component)]
-struct FlowerCountText;
+pub(crate) struct FlowerCountText;
 
 // Marker component for the text bundle that shows the countdown timer
 #[derive(Component)]
 struct GameTimerText;
 
-fn spawn_hud(mut commands: Commands, assets: Res<UiAssets>) {
+// Fonts aren't loaded when running headless, so text falls back to Bevy's built in one.
+fn font(assets: Option<&UiAssets>) -> Handle<Font> {
+    assets.map(|assets| assets.font.clone()).unwrap_or_default()
+}
+
+fn spawn_hud(mut commands: Commands, assets: Option<Res<UiAssets>>) {
+    let font = font(assets.as_deref());
     commands
         .spawn((
             GameUi,
@@ -74,7 +83,7 @@ fn spawn_hud(mut commands: Commands, assets: Res<UiAssets>) {
                 TextBundle::from_section(
                     "0 Flowers",
                     TextStyle {
-                        font: assets.font.clone(),
+                        font: font.clone(),
                         font_size: 30.,
                         ..default()
                     },
@@ -86,7 +95,7 @@ fn spawn_hud(mut commands: Commands, assets: Res<UiAssets>) {
                 TextBundle::from_section(
                     "Ready...",
                     TextStyle {
-                        font: assets.font.clone(),
+                        font: font.clone(),
                         font_size: 30.,
                         ..default()
                     },
@@ -102,7 +111,8 @@ fn cleanup_gameui(mut commands: Commands, ui: Query<Entity, With<GameUi>>) {
 }
 
 fn update_flower_count(
-    mut ui: Query<&mut Text, With<FlowerCountText>>,
+    mut commands: Commands,
+    mut ui: Query<(Entity, &mut Text), With<FlowerCountText>>,
     updates: Query<&FlowerCount, Changed<FlowerCount>>,
 ) {
     // This will only return something on frames when the flower count is updated.
@@ -110,7 +120,7 @@ fn update_flower_count(
         return;
     };
 
-    let Ok(mut ui) = ui.get_single_mut() else {
+    let Ok((entity, mut ui)) = ui.get_single_mut() else {
         error!("unable to update flower count; cannot get UI text");
         return;
     };
@@ -119,10 +129,26 @@ fn update_flower_count(
 
     if ui.as_ref().sections[0].value != flower_count_text {
         ui.sections[0].value = flower_count_text;
+
+        // Bounce the counter whenever it goes up
+        if count.0 > 0 {
+            commands.entity(entity).insert(Animator::new(Tween::new(
+                Ease::BackOut,
+                Duration::from_millis(250),
+                ScaleLens {
+                    start: Vec3::splat(1.4),
+                    end: Vec3::ONE,
+                },
+            )));
+        }
     }
 }
 
-fn update_game_timer(mut ui: Query<&mut Text, With<GameTimerText>>, timer: Res<GameTimer>) {
+fn update_game_timer(mut ui: Query<&mut Text, With<GameTimerText>>, timer: Option<Res<GameTimer>>) {
+    let Some(timer) = timer else {
+        return;
+    };
+
     let Ok(mut ui) = ui.get_single_mut() else {
         error!("unable to get game timer text to update");
         return;
@@ -144,8 +170,10 @@ pub enum GameOverButtonChoice {
 fn spawn_game_over_ui(
     mut commands: Commands,
     player: Query<&FlowerCount, With<Player>>,
-    assets: Res<UiAssets>,
+    assets: Option<Res<UiAssets>>,
 ) {
+    let font = font(assets.as_deref());
+
     let Ok(player) = player.get_single() else {
         error!("unable to get player to display game over ui");
         return;
@@ -153,6 +181,13 @@ fn spawn_game_over_ui(
 
     let flower_count = player.0;
 
+    // Drops in from above the window. Right and bottom stay unset, as a default `UiRect` would
+    // set them to zero and stretch the panel.
+    let slide_in = UiPositionLens {
+        start: UiRect::new(Val::Percent(50.), Val::Auto, Val::Percent(-25.), Val::Auto),
+        end: UiRect::new(Val::Percent(50.), Val::Auto, Val::Percent(25.), Val::Auto),
+    };
+
     commands
         .spawn((
             GameUi,
@@ -160,18 +195,23 @@ fn spawn_game_over_ui(
                 style: Style {
                     position_type: PositionType::Absolute,
                     flex_direction: FlexDirection::Column,
-                    top: Val::Percent(25.),
-                    left: Val::Percent(50.),
+                    top: slide_in.start.top,
+                    left: slide_in.start.left,
                     ..default()
                 },
                 ..default()
             },
+            Animator::new(Tween::new(
+                Ease::BackOut,
+                Duration::from_millis(500),
+                slide_in,
+            )),
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
                 "Game Over",
                 TextStyle {
-                    font: assets.font.clone(),
+                    font: font.clone(),
                     font_size: 30.,
                     ..default()
                 },
@@ -181,7 +221,7 @@ fn spawn_game_over_ui(
             parent.spawn(TextBundle::from_section(
                 message,
                 TextStyle {
-                    font: assets.font.clone(),
+                    font: font.clone(),
                     ..default()
                 },
             ));
@@ -202,7 +242,7 @@ fn spawn_game_over_ui(
                             parent.spawn(TextBundle::from_section(
                                 "Retry",
                                 TextStyle {
-                                    font: assets.font.clone(),
+                                    font: font.clone(),
                                     ..default()
                                 },
                             ));
@@ -214,7 +254,7 @@ fn spawn_game_over_ui(
                             parent.spawn(TextBundle::from_section(
                                 "Exit",
                                 TextStyle {
-                                    font: assets.font.clone(),
+                                    font: font.clone(),
                                     ..default()
                                 },
                             ));
@@ -224,7 +264,7 @@ fn spawn_game_over_ui(
 }
 
 // Spawns a button with a default style
-fn spawn_button<'a>(parent: &'a mut ChildBuilder) -> EntityCommands<'a> {
+pub(crate) fn spawn_button<'a>(parent: &'a mut ChildBuilder) -> EntityCommands<'a> {
     parent.spawn(ButtonBundle {
         style: Style {
             padding: UiRect::new(Val::Px(20.), Val::Px(20.), Val::Px(12.), Val::Px(12.)),
@@ -0,0 +1,186 @@
+use std::time::Duration;
+
+use bevy::math::UVec2;
+use super_waddle::aseprite::{AsepriteFile, AsepriteLoadError};
+
+fn load(path: &str) -> AsepriteFile {
+    AsepriteFile::parse(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn opaque_pixels(file: &AsepriteFile, frame: usize) -> usize {
+    file.render_frame(frame)
+        .chunks(4)
+        .filter(|pixel| pixel[3] > 0)
+        .count()
+}
+
+#[test]
+fn reads_frames_layers_and_tags() {
+    let goose = load("assets/sprites/goose.aseprite");
+
+    assert_eq!((goose.width, goose.height), (64, 64));
+    let layers = goose
+        .layers
+        .iter()
+        .map(|layer| layer.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(layers, ["Back Foot", "Front Foot", "Body", "Head and Neck"]);
+
+    let durations = goose
+        .frames
+        .iter()
+        .map(|frame| frame.duration)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        durations,
+        [100, 200, 200].map(Duration::from_millis).to_vec()
+    );
+
+    let tags = goose
+        .tags
+        .iter()
+        .map(|tag| (tag.name.as_str(), tag.from, tag.to))
+        .collect::<Vec<_>>();
+    assert_eq!(tags, [("Idle", 0, 0), ("Walk", 1, 2)]);
+}
+
+// The counts match the PNGs Aseprite exported for the same frames
+#[test]
+fn flattens_every_layer() {
+    let goose = load("assets/sprites/goose.aseprite");
+    assert_eq!(opaque_pixels(&goose, 0), 1425);
+
+    let flower = load("assets/sprites/flower_blue.aseprite");
+    assert_eq!(flower.layers.len(), 5);
+    assert_eq!(opaque_pixels(&flower, 0), 1768);
+}
+
+#[test]
+fn lays_frames_out_in_an_atlas() {
+    let flower = load("assets/sprites/spike_flower.aseprite");
+    let (image, layout) = flower.atlas();
+
+    assert_eq!(layout.len(), flower.frames.len());
+    for (frame, rect) in layout.textures.iter().enumerate() {
+        assert_eq!(
+            rect.size().as_uvec2(),
+            UVec2::new(flower.width, flower.height)
+        );
+        assert!(rect.max.x <= image.width() as f32 && rect.max.y <= image.height() as f32);
+
+        // Each frame's pixels end up where its rect says
+        let pixels = flower.render_frame(frame);
+        let row = (flower.width * 4) as usize;
+        for y in 0..flower.height {
+            let start =
+                (((rect.min.y as u32 + y) * image.width() + rect.min.x as u32) * 4) as usize;
+            let expected = &pixels[y as usize * row..(y as usize + 1) * row];
+            assert_eq!(&image.data[start..start + row], expected);
+        }
+    }
+}
+
+#[test]
+fn rejects_other_files() {
+    let result = AsepriteFile::parse(&std::fs::read("assets/sprites/goose.png").unwrap());
+    assert!(matches!(result, Err(AsepriteLoadError::NotAseprite)));
+
+    let goose = std::fs::read("assets/sprites/goose.aseprite").unwrap();
+    let result = AsepriteFile::parse(&goose[..goose.len() / 2]);
+    assert!(matches!(result, Err(AsepriteLoadError::Truncated)));
+}
+
+// A 16x16 RGBA file with one layer, and one cel per frame made from the given cel chunk data
+fn file_with_cels(cels: &[Vec<u8>]) -> Vec<u8> {
+    let chunk = |chunk_type: u16, data: &[u8]| {
+        let mut chunk = ((data.len() + 6) as u32).to_le_bytes().to_vec();
+        chunk.extend(chunk_type.to_le_bytes());
+        chunk.extend(data);
+        chunk
+    };
+
+    let mut layer = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 0];
+    layer.extend(5u16.to_le_bytes());
+    layer.extend(b"Layer");
+
+    let mut bytes = vec![0; 128];
+    bytes[4..6].copy_from_slice(&0xA5E0u16.to_le_bytes());
+    bytes[6..8].copy_from_slice(&(cels.len() as u16).to_le_bytes());
+    bytes[8..10].copy_from_slice(&16u16.to_le_bytes());
+    bytes[10..12].copy_from_slice(&16u16.to_le_bytes());
+    bytes[12..14].copy_from_slice(&32u16.to_le_bytes());
+
+    for (index, cel) in cels.iter().enumerate() {
+        let mut chunks = Vec::new();
+        if index == 0 {
+            chunks.extend(chunk(0x2004, &layer));
+        }
+        chunks.extend(chunk(0x2005, cel));
+        let count = if index == 0 { 2u32 } else { 1 };
+
+        bytes.extend(((chunks.len() + 16) as u32).to_le_bytes());
+        bytes.extend(0xF1FAu16.to_le_bytes());
+        bytes.extend((count as u16).to_le_bytes());
+        bytes.extend(100u16.to_le_bytes());
+        bytes.extend([0, 0]);
+        bytes.extend(count.to_le_bytes());
+        bytes.extend(chunks);
+    }
+
+    bytes
+}
+
+// Layer 0 at 0, 0 and fully opaque, then the rest of the cel
+fn cel(cel_type: u16, rest: &[u8]) -> Vec<u8> {
+    let mut cel = vec![0, 0, 0, 0, 0, 0, 255];
+    cel.extend(cel_type.to_le_bytes());
+    cel.extend([0; 7]);
+    cel.extend(rest);
+    cel
+}
+
+#[test]
+fn rejects_broken_files() {
+    // Two frames whose cels link to each other, with no pixels anywhere
+    let looped = file_with_cels(&[cel(1, &1u16.to_le_bytes()), cel(1, &0u16.to_le_bytes())]);
+    let result = AsepriteFile::parse(&looped);
+    assert!(matches!(
+        result,
+        Err(AsepriteLoadError::BrokenLink { frame: 0, layer: 0 })
+    ));
+
+    let missing = file_with_cels(&[cel(1, &7u16.to_le_bytes())]);
+    let result = AsepriteFile::parse(&missing);
+    assert!(matches!(result, Err(AsepriteLoadError::BrokenLink { .. })));
+
+    // 65535x65535 pixels, far bigger than any canvas
+    let huge = file_with_cels(&[cel(0, &[0xFF, 0xFF, 0xFF, 0xFF])]);
+    let result = AsepriteFile::parse(&huge);
+    assert!(matches!(result, Err(AsepriteLoadError::CelTooLarge)));
+}
+
+#[test]
+fn rejects_oversized_sprites() {
+    let mut canvas = file_with_cels(&[cel(0, &[0, 0, 0, 0])]);
+    canvas[8..10].copy_from_slice(&5000u16.to_le_bytes());
+    let result = AsepriteFile::parse(&canvas);
+    assert!(matches!(
+        result,
+        Err(AsepriteLoadError::CanvasTooLarge {
+            width: 5000,
+            height: 16
+        })
+    ));
+
+    // Each frame fits, but five of them are laid out three across
+    let mut frames = file_with_cels(&vec![cel(0, &[0, 0, 0, 0]); 5]);
+    frames[8..12].copy_from_slice(&[0, 16, 0, 16]);
+    let result = AsepriteFile::parse(&frames);
+    assert!(matches!(
+        result,
+        Err(AsepriteLoadError::AtlasTooLarge {
+            width: 12288,
+            height: 8192
+        })
+    ));
+}
@@ -0,0 +1,34 @@
+use serde_json::Value;
+use super_waddle::assets::GameConfigFile;
+
+fn read_json(path: &str) -> Value {
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+// Regenerate it with `cargo run --bin super-waddle-config schema > assets/game_config.schema.json`
+#[test]
+fn shipped_schema_matches_config_struct() {
+    let generated: Value = serde_json::from_str(&GameConfigFile::schema_json()).unwrap();
+    assert_eq!(read_json("assets/game_config.schema.json"), generated);
+}
+
+#[test]
+fn shipped_config_is_valid() {
+    let config = GameConfigFile::from_file("assets/game_config.json").unwrap();
+    assert_eq!(config.schema.as_deref(), Some("./game_config.schema.json"));
+    assert!(config.validate().is_empty(), "{:?}", config.validate());
+}
+
+#[test]
+fn validation_reports_out_of_range_values() {
+    let mut config = GameConfigFile::from_file("assets/game_config.json").unwrap();
+    config.player_move_speed = 0.;
+    config.flower_pickup_range = -1.;
+
+    let fields = config
+        .validate()
+        .into_iter()
+        .map(|problem| problem.field)
+        .collect::<Vec<_>>();
+    assert_eq!(fields, ["player_move_speed", "flower_pickup_range"]);
+}
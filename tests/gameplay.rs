@@ -0,0 +1,253 @@
+use bevy::{ecs::query::QueryFilter, prelude::*};
+use super_waddle::{
+    assets::GameConfigFile,
+    autopilot::Strategy,
+    enemy::Enemy,
+    game::GameObject,
+    headless::HeadlessApp,
+    input::{InputSource, InputState},
+    player::{Facing, Player},
+    ui::{GameOverButtonChoice, UiPlugin},
+    GameState,
+};
+
+fn config(num_flowers: u32, game_time_seconds: f32) -> GameConfigFile {
+    GameConfigFile {
+        schema: None,
+        player_move_speed: 250.,
+        world_size: 1024.,
+        num_flowers,
+        flower_pickup_range: 16.,
+        game_time_seconds,
+        simulation_hz: 64.,
+    }
+}
+
+// The player never moves in these tests, so without a pickup range no flowers get picked and counts
+// stay predictable.
+fn config_without_pickups(num_flowers: u32, game_time_seconds: f32) -> GameConfigFile {
+    GameConfigFile {
+        flower_pickup_range: 0.,
+        ..config(num_flowers, game_time_seconds)
+    }
+}
+
+fn count<F: QueryFilter>(app: &mut HeadlessApp) -> usize {
+    let world = app.world_mut();
+    world.query_filtered::<Entity, F>().iter(world).count()
+}
+
+fn entities<F: QueryFilter>(app: &mut HeadlessApp) -> Vec<Entity> {
+    let world = app.world_mut();
+    world.query_filtered::<Entity, F>().iter(world).collect()
+}
+
+fn spawn_flower(app: &mut HeadlessApp, x: f32, y: f32) -> Entity {
+    app.world_mut()
+        .spawn((
+            Enemy,
+            GameObject,
+            TransformBundle::from_transform(Transform::from_xyz(x, y, 0.)),
+        ))
+        .id()
+}
+
+fn retry(app: &mut HeadlessApp) {
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Gaming);
+    app.step(1);
+}
+
+#[test]
+fn spawns_configured_number_of_flowers() {
+    let mut app = HeadlessApp::new(config_without_pickups(37, 10.));
+    app.step(1);
+
+    assert_eq!(app.state(), GameState::Gaming);
+    assert_eq!(count::<With<Enemy>>(&mut app), 37);
+    assert_eq!(count::<With<Player>>(&mut app), 1);
+}
+
+#[test]
+fn loads_config_from_disk() {
+    let expected = GameConfigFile::from_file("assets/game_config.json").unwrap();
+    let mut app = HeadlessApp::from_config_file("assets/game_config.json").unwrap();
+    app.step(1);
+
+    // Any flowers that happened to spawn next to the player have already been picked
+    let picked = app.flower_count().unwrap() as usize;
+    assert_eq!(
+        count::<With<Enemy>>(&mut app) + picked,
+        expected.num_flowers as usize
+    );
+}
+
+#[test]
+fn picking_a_flower_counts_it_exactly_once() {
+    let mut app = HeadlessApp::new(config(0, 10.));
+    app.step(1);
+
+    // The player starts at the origin; three flowers in range and one out of it
+    let near = [
+        spawn_flower(&mut app, 0., 0.),
+        spawn_flower(&mut app, 10., 0.),
+        spawn_flower(&mut app, 0., -12.),
+    ];
+    let far = spawn_flower(&mut app, 500., 500.);
+
+    app.step(10);
+
+    assert_eq!(app.flower_count(), Some(3));
+    for flower in near {
+        assert!(app.world().get_entity(flower).is_none());
+    }
+    assert!(app.world().get_entity(far).is_some());
+}
+
+#[test]
+fn timer_expiry_ends_the_game() {
+    let mut app = HeadlessApp::new(config(10, 1.));
+
+    // Half a second in at 64 ticks per second
+    app.step(32);
+    assert_eq!(app.state(), GameState::Gaming);
+
+    app.step(40);
+    assert_eq!(app.state(), GameState::GameOver);
+}
+
+// The HUD keeps updating for the rest of the frame the timer runs out in, before the game over
+// screen replaces it.
+#[test]
+fn ui_survives_timer_expiry() {
+    let mut app = HeadlessApp::new(config(10, 1.));
+    app.add_plugins(UiPlugin);
+
+    app.step(72);
+    assert_eq!(app.state(), GameState::GameOver);
+    assert_eq!(count::<With<GameOverButtonChoice>>(&mut app), 2);
+
+    retry(&mut app);
+    app.step(72);
+    assert_eq!(app.state(), GameState::GameOver);
+}
+
+#[test]
+fn retry_starts_a_fresh_game() {
+    let mut app = HeadlessApp::new(config_without_pickups(20, 1.));
+    app.step(1);
+    // Right on top of the player, so it's picked even without a pickup range
+    spawn_flower(&mut app, 0., 0.);
+    app.step(80);
+    assert_eq!(app.state(), GameState::GameOver);
+    assert_eq!(app.flower_count(), Some(1));
+
+    retry(&mut app);
+
+    assert_eq!(app.state(), GameState::Gaming);
+    assert_eq!(app.flower_count(), Some(0));
+    assert_eq!(count::<With<Player>>(&mut app), 1);
+    assert_eq!(count::<With<Enemy>>(&mut app), 20);
+}
+
+#[test]
+fn cleanup_removes_every_game_object() {
+    let mut app = HeadlessApp::new(config_without_pickups(20, 1.));
+    app.step(80);
+    assert_eq!(app.state(), GameState::GameOver);
+
+    let before = entities::<With<GameObject>>(&mut app);
+    assert_eq!(before.len(), 21);
+
+    retry(&mut app);
+
+    // Only the newly spawned player and flowers are left
+    for entity in before {
+        assert!(app.world().get_entity(entity).is_none());
+    }
+    assert_eq!(count::<With<GameObject>>(&mut app), 21);
+}
+
+#[test]
+fn autopilot_collects_flowers() {
+    for strategy in [Strategy::NearestFlower, Strategy::PlannedRoute] {
+        let mut app = HeadlessApp::new(GameConfigFile {
+            world_size: 256.,
+            ..config(50, 5.)
+        });
+        app.enable_autopilot(strategy);
+        app.step(400);
+
+        assert_eq!(app.state(), GameState::GameOver);
+        assert!(
+            app.flower_count().unwrap() > 0,
+            "{:?} didn't collect anything",
+            strategy
+        );
+    }
+}
+
+fn player_scale(app: &mut HeadlessApp) -> f32 {
+    let world = app.world_mut();
+    world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world)
+        .scale
+        .x
+}
+
+#[test]
+fn goose_turns_to_face_where_it_walks() {
+    let mut app = HeadlessApp::new(config_without_pickups(0, 10.));
+    app.step(1);
+
+    // Nothing else writes the input while it's in playback, so the target stays put
+    let world = app.world_mut();
+    world.insert_resource(InputSource::Playback);
+    world
+        .resource_mut::<InputState>()
+        .set_target(Vec2::new(500., 0.));
+
+    // A couple of frames in it's facing right, but still part way through turning round
+    app.step(2);
+    let world = app.world_mut();
+    let (facing, transform) = world
+        .query_filtered::<(&Facing, &Transform), With<Player>>()
+        .single(world);
+    assert_eq!(**facing, Vec2::X);
+    assert!(transform.scale.x > -1. && transform.scale.x < 1.);
+
+    app.step(20);
+    let world = app.world_mut();
+    let transform = world
+        .query_filtered::<&Transform, With<Player>>()
+        .single(world);
+    assert_eq!(transform.scale.x, -1.);
+    assert!(transform.translation.x > 0.);
+}
+
+// A fresh goose starts out looking left, so it has to turn again even though the last one had
+// already turned right.
+#[test]
+fn goose_turns_again_after_retry() {
+    let mut app = HeadlessApp::new(config_without_pickups(0, 1.));
+    app.step(1);
+    app.world_mut().insert_resource(InputSource::Playback);
+
+    app.world_mut()
+        .resource_mut::<InputState>()
+        .set_target(Vec2::new(500., 0.));
+    app.step(20);
+    assert_eq!(player_scale(&mut app), -1.);
+
+    app.step(80);
+    assert_eq!(app.state(), GameState::GameOver);
+    retry(&mut app);
+
+    app.world_mut()
+        .resource_mut::<InputState>()
+        .set_target(Vec2::new(500., 0.));
+    app.step(20);
+    assert_eq!(player_scale(&mut app), -1.);
+}
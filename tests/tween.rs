@@ -0,0 +1,180 @@
+use std::time::Duration;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*, time::TimeUpdateStrategy};
+use super_waddle::tween::{
+    Animator, Ease, Repeat, TranslationLens, Tween, TweenCompleted, TweenPlugin,
+};
+
+const FRAME: Duration = Duration::from_millis(10);
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, TweenPlugin))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    // Get startup out of the way, so every frame after this moves time on by exactly `FRAME`
+    app.update();
+    app
+}
+
+fn step(app: &mut App, frames: u32) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+// Slides from `start` to `end` along x over `millis`
+fn slide(start: f32, end: f32, millis: u64) -> Tween<Transform> {
+    Tween::new(
+        Ease::Linear,
+        Duration::from_millis(millis),
+        TranslationLens {
+            start: Vec3::X * start,
+            end: Vec3::X * end,
+        },
+    )
+}
+
+fn x(app: &App, entity: Entity) -> f32 {
+    app.world.get::<Transform>(entity).unwrap().translation.x
+}
+
+fn is_animating(app: &App, entity: Entity) -> bool {
+    app.world.get::<Animator<Transform>>(entity).is_some()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "expected {expected}, got {actual}"
+    );
+}
+
+#[test]
+fn ease_curves_go_from_start_to_end() {
+    let halfway = [
+        (Ease::Linear, 0.5),
+        (Ease::QuadIn, 0.25),
+        (Ease::QuadOut, 0.75),
+        (Ease::QuadInOut, 0.5),
+        (Ease::CubicIn, 0.125),
+        (Ease::CubicOut, 0.875),
+        (Ease::CubicInOut, 0.5),
+        (Ease::SineInOut, 0.5),
+        (Ease::BackOut, 1.0876975),
+        (Ease::BounceOut, 0.765625),
+    ];
+
+    for (ease, expected) in halfway {
+        assert_close(ease.apply(0.), 0.);
+        assert_close(ease.apply(0.5), expected);
+        assert_close(ease.apply(1.), 1.);
+
+        // Outside 0 to 1 it stays at the ends
+        assert_close(ease.apply(-1.), 0.);
+        assert_close(ease.apply(2.), 1.);
+    }
+}
+
+#[test]
+fn ping_pong_turns_around_at_the_end() {
+    let mut app = app();
+    let entity = app
+        .world
+        .spawn((
+            Transform::default(),
+            Animator::new(
+                slide(0., 1., 100)
+                    .with_repeat(Repeat::Times(2))
+                    .with_ping_pong(),
+            ),
+        ))
+        .id();
+
+    step(&mut app, 5);
+    assert_close(x(&app, entity), 0.5);
+    step(&mut app, 5);
+    assert_close(x(&app, entity), 1.);
+    step(&mut app, 5);
+    assert_close(x(&app, entity), 0.5);
+    assert!(is_animating(&app, entity));
+
+    // Ends back where it started
+    step(&mut app, 5);
+    assert_close(x(&app, entity), 0.);
+    assert!(!is_animating(&app, entity));
+}
+
+#[test]
+fn repeats_restart_from_the_start() {
+    let mut app = app();
+    let three_times = app
+        .world
+        .spawn((
+            Transform::default(),
+            Animator::new(slide(0., 1., 100).with_repeat(Repeat::Times(3))),
+        ))
+        .id();
+    let forever = app
+        .world
+        .spawn((
+            Transform::default(),
+            Animator::new(slide(0., 1., 100).with_repeat(Repeat::Forever)),
+        ))
+        .id();
+
+    step(&mut app, 25);
+    assert_close(x(&app, three_times), 0.5);
+
+    step(&mut app, 5);
+    assert_close(x(&app, three_times), 1.);
+    assert!(!is_animating(&app, three_times));
+
+    step(&mut app, 75);
+    assert_close(x(&app, forever), 0.5);
+    assert!(is_animating(&app, forever));
+}
+
+#[test]
+fn then_plays_tweens_in_order() {
+    let mut app = app();
+    let entity = app
+        .world
+        .spawn((
+            Transform::default(),
+            Animator::new(slide(0., 1., 50)).then(slide(1., 3., 50)),
+        ))
+        .id();
+
+    step(&mut app, 3);
+    assert_close(x(&app, entity), 0.6);
+    step(&mut app, 4);
+    assert_close(x(&app, entity), 1.8);
+    assert!(is_animating(&app, entity));
+
+    step(&mut app, 3);
+    assert_close(x(&app, entity), 3.);
+    assert!(!is_animating(&app, entity));
+}
+
+#[test]
+fn completed_events_are_sent_once_each() {
+    let mut app = app();
+    let entity = app
+        .world
+        .spawn((
+            Transform::default(),
+            Animator::new(slide(0., 1., 30).with_completed_event(1))
+                .then(slide(1., 0., 30).with_completed_event(2)),
+        ))
+        .id();
+
+    let mut reader = ManualEventReader::<TweenCompleted>::default();
+    let mut completed = Vec::new();
+    for _ in 0..20 {
+        step(&mut app, 1);
+        let events = app.world.resource::<Events<TweenCompleted>>();
+        completed.extend(reader.read(events).map(|event| (event.entity, event.id)));
+    }
+
+    assert_eq!(completed, [(entity, 1), (entity, 2)]);
+}